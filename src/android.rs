@@ -1,24 +1,61 @@
-use crate::args::Args;
-use crate::error::Result;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use crate::args::BuildOptions;
+use crate::config::Config;
+use crate::diagnostics::list_android_devices;
+use crate::emulator;
+use crate::error::{AppError, Result};
+use crate::output::ReverseResult;
+use crate::metro::ensure_port_available;
+use crate::package_manager::PackageManager;
+use crate::runner::CommandRunner;
+use crate::version::Version;
+use crate::terminal::is_macos;
 use crate::utils::{
-    clean_install, close_terminal_windows, deep_clean, is_version_greater_or_equal,
-    kill_process_logged, launch_packager, launch_sim, watch_directory_logged,
-    LogWriter,
+    clean_install, close_terminal_windows, deep_clean, get_preferred_android_device,
+    kill_process_logged, launch_packager, launch_sim, pick_android_device, resolve_android_device, run_build_foreground,
+    wait_for_packager, watch_directory_logged, LogMetadata, LogWriter, RunOutcome,
 };
 
-pub fn run_android(args: &Args, _current_dir: &str, react_native_version: &str) -> Result<()> {
-    // Create log writer at the start to capture all output
-    let log = LogWriter::new("android")?;
+/// Run the Android app. The outcome holds the build result when running in the foreground,
+/// and Metro's readiness when rn-run started the packager itself.
+pub fn run_android(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    build: &BuildOptions,
+    json: bool,
+    project_dir: &str,
+    react_native_version: &Version,
+    package_manager: PackageManager,
+) -> Result<RunOutcome> {
+    // Create log writer at the start to capture all output.
+    // A dry run only reports what would happen, so it leaves the build logs alone.
+    let log = if runner.is_dry_run() {
+        LogWriter::discard()
+    } else {
+        LogWriter::new(LogMetadata::new(runner, "android", project_dir, react_native_version), config.max_logs())?
+            .echo_to_stderr(json)
+    };
+
+    // Never kill another project's Metro; restarting our own is fine
+    ensure_port_available(config.metro_port(), Path::new(project_dir))?;
+    kill_process_logged(runner, config.metro_port(), Some(&log))?;
 
-    kill_process_logged(Some(&log))?;
-    close_terminal_windows()?;
+    // In the foreground the build runs in this terminal, so leave its window open
+    if build.foreground {
+        log.log("[rn-run]: Skipping Terminal window cleanup (foreground run)");
+    } else if is_macos() {
+        close_terminal_windows(runner)?;
+    } else {
+        log.log("[rn-run]: Skipping Terminal window cleanup (macOS only)");
+    }
 
-    if args.upgrade {
-        deep_clean("android")?;
+    if build.upgrade {
+        deep_clean(runner, package_manager, "android", &log)?;
     }
 
-    if args.clean_install || args.upgrade {
-        clean_install(&react_native_version, "android")?;
+    if build.clean_install || build.upgrade {
+        clean_install(runner, package_manager, "android", &log)?;
     }
 
     // Get current directory for watchman
@@ -27,15 +64,222 @@ pub fn run_android(args: &Args, _current_dir: &str, react_native_version: &str)
         .to_string_lossy()
         .to_string();
 
-    watch_directory_logged(&current_dir, Some(&log))?;
+    watch_directory_logged(runner, &current_dir, Some(&log))?;
+    let config = &select_device(runner, config, !json, &log)?;
+    reverse_metro_port(runner, config, &log);
+
+    if build.foreground {
+        log.log("packager will be launched by the react-native CLI");
+        let build = run_build_foreground(runner, config, package_manager, react_native_version, "android", &log)?;
+        return Ok(RunOutcome { build: Some(build), metro: None });
+    }
 
-    if is_version_greater_or_equal(react_native_version, "0.74") {
+    let metro = if react_native_version.at_least(0, 74) {
         log.log("packager will be launched via npx");
+        None
     } else {
-        launch_packager()?;
+        launch_packager(runner, package_manager, config.metro.port)?;
+        wait_for_packager(runner, config, &log)?
+    };
+
+    let _log_path = launch_sim(runner, config, package_manager, react_native_version, "android", &log)?;
+
+    Ok(RunOutcome { build: None, metro })
+}
+
+/// Pick the device to install on and store its serial in the returned config.
+///
+/// A configured `--device` is looked up by serial or model. With several devices
+/// attached and a terminal to ask on, the user picks one; otherwise physical
+/// devices win over emulators. With no device at all, the configured AVD is
+/// started. `--all-devices` leaves the device unset, so the react-native CLI
+/// installs on every device.
+fn select_device(runner: &dyn CommandRunner, config: &Config, interactive: bool, log: &LogWriter) -> Result<Config> {
+    let mut config = config.clone();
+    if config.all_devices() {
+        log.log_green("[rn-run]: Installing on every connected device");
+        return Ok(config);
+    }
+    if let Some(wanted) = &config.android.device {
+        config.android.device = Some(resolve_android_device(runner, wanted)?);
+        return Ok(config);
+    }
+
+    let interactive = interactive && !runner.is_dry_run() && io::stdin().is_terminal();
+    let picked = if interactive { pick_android_device(&list_android_devices(runner)) } else { None };
+    if let Some(serial) = picked.or_else(|| get_preferred_android_device(runner)) {
+        config.android.device = Some(serial);
+        return Ok(config);
+    }
+
+    if let Some(avd) = config.android.avd.clone() {
+        log.log_green(&format!("[rn-run]: No device connected, starting emulator {}", avd));
+        let started = emulator::start(runner, &avd, false, emulator::BOOT_TIMEOUT)?;
+        log.log_green(&format!("[rn-run]: {}", started.message));
+        config.android.device = Some(started.serial);
+    }
+    Ok(config)
+}
+
+/// Forward the Metro port to this machine from the target device and from every
+/// connected physical device, which can't reach the bundler otherwise. A failure
+/// is logged rather than fatal: the app then shows "Could not connect to
+/// development server", which the log explains.
+fn reverse_metro_port(runner: &dyn CommandRunner, config: &Config, log: &LogWriter) {
+    let mut serials: Vec<String> = config.android.device.iter().cloned().collect();
+    for device in list_android_devices(runner) {
+        let wanted = !device.is_emulator || config.all_devices();
+        if wanted && device.state == "device" && !serials.contains(&device.serial) {
+            serials.push(device.serial);
+        }
     }
 
-    let _log_path = launch_sim(&react_native_version, args, &log)?;
+    let port = config.metro_port();
+    for serial in serials {
+        match reverse_port(runner, &serial, port) {
+            Ok(()) => log.log(&format!("[rn-run]: adb reverse tcp:{0} tcp:{0} on {1}", port, serial)),
+            Err(e) => log.log(&format!("[rn-run]: {}. The app on {} may not reach Metro", e, serial)),
+        }
+    }
+}
+
+/// Forward `port` on device `serial` to the same port on this machine, and check
+/// with `adb reverse --list` that the rule is in place.
+pub fn reverse_port(runner: &dyn CommandRunner, serial: &str, port: u16) -> Result<()> {
+    let tcp_port = format!("tcp:{}", port);
+    let output = runner
+        .output("adb", &["-s", serial, "reverse", &tcp_port, &tcp_port])
+        .map_err(|e| AppError::ReverseFailed(serial.to_string(), e.to_string()))?;
+    if !output.success {
+        let stderr = output.stderr_str();
+        return Err(AppError::ReverseFailed(serial.to_string(), stderr.trim().to_string()));
+    }
+
+    // Nothing was forwarded during a dry run, so there is nothing to verify
+    if runner.is_dry_run() {
+        return Ok(());
+    }
 
+    // Each rule is listed as "<transport> tcp:<device port> tcp:<local port>"
+    let listed = runner
+        .output("adb", &["-s", serial, "reverse", "--list"])
+        .map(|output| {
+            output.stdout_str().lines().any(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                fields.len() >= 3 && fields[1] == tcp_port && fields[2] == tcp_port
+            })
+        })
+        .unwrap_or(false);
+    if !listed {
+        return Err(AppError::ReverseFailed(serial.to_string(), format!("{} is missing from adb reverse --list", tcp_port)));
+    }
     Ok(())
 }
+
+/// `devices android reverse`: forward `port` on the device matching `device`, or
+/// on every connected device.
+pub fn reverse_devices(runner: &dyn CommandRunner, port: u16, device: Option<&str>) -> Result<ReverseResult> {
+    let serials = match device {
+        Some(device) => vec![resolve_android_device(runner, device)?],
+        None => list_android_devices(runner)
+            .into_iter()
+            .filter(|device| device.state == "device")
+            .map(|device| device.serial)
+            .collect(),
+    };
+
+    for serial in &serials {
+        reverse_port(runner, serial, port)?;
+    }
+
+    let message = match serials.len() {
+        0 => "No Android devices connected".to_string(),
+        _ => format!("Forwarded tcp:{} to this machine on {}", port, serials.join(", ")),
+    };
+    Ok(ReverseResult { port, devices: serials, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use crate::args::{Args, Command};
+    use crate::runner::FakeRunner;
+    use crate::test_support::{EnvGuard, TempDir};
+
+    fn rn_074() -> Version {
+        Version::parse("0.74.3").unwrap()
+    }
+
+    #[test]
+    fn test_run_android_launches_on_preferred_device() {
+        let home = TempDir::new("android-home");
+        let mut env = EnvGuard::home(home.path());
+        env.set("RN_RUN_TERMINAL", "tmux");
+
+        let runner = FakeRunner::new()
+            .allow_unscripted()
+            .on("adb devices", "List of devices attached\nR58M12345\tdevice\n");
+        let outcome = run_android(&runner, &Config::default(), &BuildOptions::default(), false, ".", &rn_074(), PackageManager::Npm).unwrap();
+        let calls = runner.calls();
+
+        assert!(outcome.build.is_none());
+        assert_eq!(calls[0], "git -C . rev-parse HEAD");
+        assert_eq!(calls[1], "listeners");
+        assert!(calls.iter().any(|c| c.starts_with("watchman watch-del")));
+        assert!(calls.iter().any(|c| c == "adb devices"));
+        assert!(calls.iter().any(|c| c == "adb -s R58M12345 reverse tcp:8081 tcp:8081"));
+        assert!(calls.iter().any(|c| c.starts_with("tmux new-window")
+            && c.contains("npx react-native run-android --active-arch-only --deviceId=R58M12345")));
+    }
+
+    #[test]
+    fn test_run_android_on_all_devices() {
+        let home = TempDir::new("android-home");
+        let mut env = EnvGuard::home(home.path());
+        env.set("RN_RUN_TERMINAL", "tmux");
+
+        // The device is left to the CLI, which installs on each, and the port is reversed on all of them
+        let runner = FakeRunner::new()
+            .allow_unscripted()
+            .on("adb devices -l", "List of devices attached\nR58M12345 device usb:1-1\nemulator-5554 device\n");
+        let args = Args::parse_from(["rn-run", "run", "android", "--all-devices"]);
+        let config = match args.resolve_command() {
+            Some((Command::Run { target }, _)) => Config::default().apply_run(&target),
+            other => panic!("unexpected command: {:?}", other),
+        };
+        run_android(&runner, &config, &BuildOptions::default(), true, ".", &rn_074(), PackageManager::Npm).unwrap();
+        let calls = runner.calls();
+        assert!(calls.iter().any(|c| c == "adb -s R58M12345 reverse tcp:8081 tcp:8081"));
+        assert!(calls.iter().any(|c| c == "adb -s emulator-5554 reverse tcp:8081 tcp:8081"));
+        assert!(calls.iter().any(|c| c.contains("run-android --active-arch-only")));
+        assert!(!calls.iter().any(|c| c.contains("--deviceId")));
+    }
+
+    fn two_phones() -> FakeRunner {
+        FakeRunner::new()
+            .on("adb devices -l", "List of devices attached\nR58M12345 device usb:1-1 model:SM_G973F\n33011FDH device usb:1-2 model:Pixel_7\n0A1B2C offline\n")
+            .on("adb -s R58M12345 reverse tcp:8082 tcp:8082", "")
+            .on("adb -s R58M12345 reverse --list", "UsbFfs tcp:8082 tcp:8082\n")
+            .on("adb -s 33011FDH reverse tcp:8082 tcp:8082", "")
+            .on("adb -s 33011FDH reverse --list", "UsbFfs tcp:8081 tcp:8081\n")
+    }
+
+    #[test]
+    fn test_reverse_port_is_verified() {
+        let runner = two_phones();
+        assert!(reverse_port(&runner, "R58M12345", 8082).is_ok());
+        // adb can exit 0 without the rule taking effect
+        assert!(matches!(reverse_port(&runner, "33011FDH", 8082), Err(AppError::ReverseFailed(serial, _)) if serial == "33011FDH"));
+    }
+
+    #[test]
+    fn test_reverse_devices() {
+        let runner = two_phones();
+        let result = reverse_devices(&runner, 8082, Some("SM_G973F")).unwrap();
+        assert_eq!(result.devices, ["R58M12345"]);
+        // Without a device every ready one is forwarded, and the first failure is reported
+        assert!(reverse_devices(&runner, 8082, None).is_err());
+        assert!(!runner.calls().iter().any(|call| call.starts_with("adb -s 0A1B2C")));
+    }
+}
@@ -1,4 +1,8 @@
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
+use crate::package_manager::PackageManager;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use crate::utils::LogStatus;
 
 /// Run react native app on ios or android.
 ///
@@ -6,124 +10,829 @@ use clap::Parser;
 /// See CLAUDE.md for detailed usage patterns and error handling guides.
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(group(ArgGroup::new("action").multiple(false)))]
+#[command(group(ArgGroup::new("cleanup").multiple(true).conflicts_with("action")))]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     // ═══════════════════════════════════════════════════════════════════════════
-    // PLATFORM SELECTION
+    // LEGACY PLATFORM FLAGS (deprecated, use `rn-run run ios|android`)
     // ═══════════════════════════════════════════════════════════════════════════
 
-    /// Run iOS app on simulator
-    #[arg(short, long, help_heading = "Platform")]
+    /// Run iOS app on simulator [deprecated: use `rn-run run ios`]
+    #[arg(short, long, hide = true, conflicts_with_all = ["android", "action", "cleanup"])]
     pub ios: bool,
 
-    /// Run Android app on emulator
-    #[arg(short, long, help_heading = "Platform")]
+    /// Run Android app on emulator [deprecated: use `rn-run run android`]
+    #[arg(short, long, hide = true, conflicts_with_all = ["action", "cleanup"])]
     pub android: bool,
 
-    /// iOS simulator name (default: "iPhone 15")
-    #[arg(short, long, help_heading = "Platform")]
+    /// iOS simulator name [deprecated: use `rn-run run ios -s`]
+    #[arg(short, long, hide = true)]
     pub simulator: Option<String>,
 
+    /// Xcode scheme to build [deprecated: use `rn-run run ios --scheme`]
+    #[arg(long, hide = true)]
+    pub scheme: Option<String>,
+
+    /// Build variant to build [deprecated: use `rn-run run android --variant`]
+    #[arg(long, hide = true)]
+    pub variant: Option<String>,
+
+    /// Clean install [deprecated: use `rn-run run ios|android -c`]
+    #[arg(short, long, hide = true)]
+    pub clean_install: bool,
+
+    /// Deep clean for RN upgrades [deprecated: use `rn-run run ios|android -u`]
+    #[arg(short, long, hide = true)]
+    pub upgrade: bool,
+
+    /// Run the build in the foreground [deprecated: use `rn-run run ios|android --foreground`]
+    #[arg(long, alias = "headless", hide = true)]
+    pub foreground: bool,
+
     // ═══════════════════════════════════════════════════════════════════════════
     // BUILD OPTIONS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    /// Clean install: delete node_modules, reinstall, pod install
-    #[arg(short, long, help_heading = "Build Options")]
-    pub clean_install: bool,
+    /// Package manager to use instead of detecting it from lockfiles
+    #[arg(long, value_enum, help_heading = "Build Options")]
+    pub package_manager: Option<PackageManager>,
 
-    /// Deep clean for RN upgrades (removes all caches and locks)
-    #[arg(short, long, help_heading = "Build Options")]
-    pub upgrade: bool,
+    /// Metro port for this project (default: from config, or 8081)
+    #[arg(long, global = true, help_heading = "Build Options")]
+    pub port: Option<u16>,
 
     // ═══════════════════════════════════════════════════════════════════════════
     // OUTPUT FORMAT
     // ═══════════════════════════════════════════════════════════════════════════
 
     /// Output in JSON format (for AI/automation)
-    #[arg(long, help_heading = "Output")]
+    #[arg(long, global = true, help_heading = "Output")]
     pub json: bool,
 
+    /// Show what would be deleted, killed and executed without changing anything
+    #[arg(long, global = true, help_heading = "Output")]
+    pub dry_run: bool,
+
     // ═══════════════════════════════════════════════════════════════════════════
     // DIAGNOSTICS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    /// Check development environment setup
-    #[arg(long, help_heading = "Diagnostics")]
+    /// Check development environment setup [deprecated: use `rn-run env check`]
+    #[arg(long, hide = true, group = "action")]
     pub check_env: bool,
 
     /// Show React Native version from package.json
-    #[arg(long, help_heading = "Diagnostics")]
+    #[arg(long, help_heading = "Diagnostics", group = "action")]
     pub rn_version: bool,
 
-    /// List available iOS simulators
-    #[arg(long, help_heading = "Diagnostics")]
+    /// List available iOS simulators [deprecated: use `rn-run devices list ios`]
+    #[arg(long, hide = true, group = "action")]
     pub list_simulators: bool,
 
-    /// List available Android emulators
-    #[arg(long, help_heading = "Diagnostics")]
+    /// List available Android emulators [deprecated: use `rn-run devices list android`]
+    #[arg(long, hide = true, group = "action")]
     pub list_emulators: bool,
 
+    /// Show the effective config (.rn-run.toml, user config and CLI flags merged)
+    #[arg(long, help_heading = "Diagnostics", group = "action")]
+    pub print_config: bool,
+
     // ═══════════════════════════════════════════════════════════════════════════
     // PROCESS MANAGEMENT
     // ═══════════════════════════════════════════════════════════════════════════
 
-    /// Kill Metro bundler on port 8081
-    #[arg(long, help_heading = "Process Management")]
+    /// Kill Metro bundler on the configured port (--port, default 8081)
+    #[arg(long, help_heading = "Process Management", group = "action")]
     pub kill_metro: bool,
 
     /// Quit iOS Simulator app
-    #[arg(long, help_heading = "Process Management")]
+    #[arg(long, help_heading = "Process Management", group = "action")]
     pub quit_simulator: bool,
 
-    /// Take screenshot of simulator/emulator
-    #[arg(long, help_heading = "Process Management")]
+    /// Take screenshot of simulator/emulator [deprecated: use `rn-run screenshot`]
+    #[arg(long, hide = true, conflicts_with_all = ["action", "cleanup"])]
     pub screenshot: bool,
 
-    /// Output path for screenshot
-    #[arg(long, help_heading = "Process Management")]
+    /// Output path for screenshot [deprecated: use `rn-run screenshot -o`]
+    #[arg(long, hide = true, requires = "screenshot")]
     pub output: Option<String>,
 
     /// Update rn-run to latest version
-    #[arg(long, visible_alias = "self-update", help_heading = "Process Management")]
+    #[arg(long, visible_alias = "self-update", help_heading = "Process Management", group = "action")]
     pub update: bool,
 
-    /// List recent build logs
-    #[arg(long, help_heading = "Process Management")]
+    /// List recent build logs [deprecated: use `rn-run logs list`]
+    #[arg(long, hide = true, group = "action")]
     pub logs: bool,
 
-    /// Show most recent build log
-    #[arg(long, help_heading = "Process Management")]
+    /// Show most recent build log [deprecated: use `rn-run logs show`]
+    #[arg(long, hide = true, group = "action")]
     pub show_log: bool,
 
     // ═══════════════════════════════════════════════════════════════════════════
     // CLEANUP
     // ═══════════════════════════════════════════════════════════════════════════
 
-    /// Delete node_modules only
-    #[arg(long, help_heading = "Cleanup")]
+    /// Delete node_modules only [deprecated: use `rn-run clean modules`]
+    #[arg(long, hide = true, group = "cleanup")]
     pub clean_modules: bool,
 
-    /// Clean iOS Pods, Podfile.lock, build
-    #[arg(long, help_heading = "Cleanup")]
+    /// Clean iOS Pods, Podfile.lock, build [deprecated: use `rn-run clean pods`]
+    #[arg(long, hide = true, group = "cleanup")]
     pub clean_pods: bool,
 
-    /// Clean Android Gradle caches
-    #[arg(long, help_heading = "Cleanup")]
+    /// Clean Android Gradle caches [deprecated: use `rn-run clean gradle`]
+    #[arg(long, hide = true, group = "cleanup")]
     pub clean_gradle: bool,
 
-    /// Clear Metro bundler cache
-    #[arg(long, help_heading = "Cleanup")]
+    /// Clear Metro bundler cache [deprecated: use `rn-run clean metro`]
+    #[arg(long, hide = true, group = "cleanup")]
     pub clean_metro: bool,
 
     /// Delete ALL iOS simulators (use with caution)
-    #[arg(long, help_heading = "Cleanup")]
+    #[arg(long, help_heading = "Cleanup", group = "action")]
     pub delete_simulators: bool,
 
     /// Delete ALL Android emulators (use with caution)
-    #[arg(long, help_heading = "Cleanup")]
+    #[arg(long, help_heading = "Cleanup", group = "action")]
     pub delete_emulators: bool,
 
-    /// Run pod install in ios/ directory
-    #[arg(long, help_heading = "Cleanup")]
+    /// Run pod install in ios/ directory (after any cleanup flags)
+    #[arg(long, help_heading = "Cleanup", group = "cleanup")]
     pub pod_install: bool,
 }
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Build and run the app
+    Run {
+        #[command(subcommand)]
+        target: RunTarget,
+    },
+    /// Delete build artifacts and caches, optionally reinstalling pods afterwards
+    Clean {
+        /// What to clean; several targets run in one pass
+        #[arg(value_enum, required_unless_present = "pod_install")]
+        targets: Vec<CleanTarget>,
+
+        /// Run pod install in ios/ after cleaning
+        #[arg(long)]
+        pod_install: bool,
+    },
+    /// Inspect the development environment
+    Env {
+        #[command(subcommand)]
+        command: EnvCommand,
+    },
+    /// Manage simulators, emulators and devices
+    Devices {
+        #[command(subcommand)]
+        command: DevicesCommand,
+    },
+    /// Inspect build logs
+    Logs {
+        #[command(subcommand)]
+        command: LogsCommand,
+    },
+    /// Inspect Metro bundler servers
+    Metro {
+        #[command(subcommand)]
+        command: MetroCommand,
+    },
+    /// Explain a known build error and how to fix it
+    Explain {
+        /// Known error id, as shown in suggested fixes (default: list all)
+        id: Option<String>,
+    },
+    /// Take a screenshot of the booted simulator or connected Android device
+    Screenshot {
+        /// Platform to capture
+        #[arg(value_enum, default_value = "ios")]
+        platform: Platform,
+
+        /// Output path (default: screenshot-<platform>-<timestamp>.png)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RunTarget {
+    /// Run the iOS app on a simulator (macOS only)
+    Ios(IosRunArgs),
+    /// Run the Android app on a device or emulator
+    Android(AndroidRunArgs),
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct IosRunArgs {
+    /// iOS simulator name (default: from config, or "iPhone 15")
+    #[arg(short, long)]
+    pub simulator: Option<String>,
+
+    /// Physical iOS device name or UDID to run on instead of a simulator
+    #[arg(long, conflicts_with = "simulator")]
+    pub device: Option<String>,
+
+    /// Xcode scheme to build
+    #[arg(long)]
+    pub scheme: Option<String>,
+
+    #[command(flatten)]
+    pub build: BuildOptions,
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct AndroidRunArgs {
+    /// Build variant to build, e.g. "devDebug"
+    #[arg(long)]
+    pub variant: Option<String>,
+
+    /// Device to install on, by serial or model (default: asks when several are connected)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Build once and install and launch on every connected device
+    #[arg(long, conflicts_with = "device")]
+    pub all_devices: bool,
+
+    /// AVD to start and wait for when no device is connected
+    #[arg(long)]
+    pub avd: Option<String>,
+
+    #[command(flatten)]
+    pub build: BuildOptions,
+}
+
+/// Options shared by `run ios` and `run android`.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// Clean install: delete node_modules, reinstall, pod install
+    #[arg(short, long)]
+    pub clean_install: bool,
+
+    /// Deep clean for RN upgrades (removes all caches and locks)
+    #[arg(short, long)]
+    pub upgrade: bool,
+
+    /// Run the build in this terminal, stream its output and exit with its status (for CI/automation)
+    #[arg(long, visible_alias = "headless")]
+    pub foreground: bool,
+
+    /// Package manager to use instead of detecting it from lockfiles
+    #[arg(long, value_enum)]
+    pub package_manager: Option<PackageManager>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CleanTarget {
+    /// Delete node_modules
+    Modules,
+    /// Delete ios/Pods, ios/Podfile.lock and ios/build
+    Pods,
+    /// Stop Gradle daemons and delete Android build dirs and the Gradle cache for the wrapper version
+    Gradle,
+    /// Clear the Metro bundler cache
+    Metro,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq)]
+pub enum EnvCommand {
+    /// Check development environment setup
+    Check,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum DevicesCommand {
+    /// List iOS simulators and Android emulators
+    List {
+        /// Only list devices for this platform
+        #[arg(value_enum)]
+        platform: Option<Platform>,
+    },
+    /// Boot, shut down, erase and create iOS simulators (macOS only)
+    Ios {
+        #[command(subcommand)]
+        command: IosDeviceCommand,
+    },
+    /// Start and stop Android emulators
+    Android {
+        #[command(subcommand)]
+        command: AndroidDeviceCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum AndroidDeviceCommand {
+    /// Start an emulator and wait until Android has booted
+    Start {
+        /// AVD name (default: avd from config)
+        avd: Option<String>,
+
+        /// Run without a window, audio or boot animation (for CI)
+        #[arg(long)]
+        headless: bool,
+    },
+    /// Shut down a running emulator
+    Stop {
+        /// AVD name or serial such as emulator-5554 (default: avd from config)
+        emulator: Option<String>,
+    },
+    /// Forward the Metro port from devices to this machine with adb reverse
+    Reverse {
+        /// Device serial or model (default: every connected device)
+        device: Option<String>,
+    },
+}
+
+impl AndroidDeviceCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AndroidDeviceCommand::Start { .. } => "start",
+            AndroidDeviceCommand::Stop { .. } => "stop",
+            AndroidDeviceCommand::Reverse { .. } => "reverse",
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum IosDeviceCommand {
+    /// Boot a simulator
+    Boot {
+        /// Simulator name or UDID (default: from config, or "iPhone 15")
+        simulator: Option<String>,
+    },
+    /// Shut down a running simulator
+    Shutdown {
+        /// Simulator name or UDID (default: from config, or "iPhone 15")
+        simulator: Option<String>,
+    },
+    /// Erase a simulator's contents and settings
+    Erase {
+        /// Simulator name or UDID (default: from config, or "iPhone 15")
+        simulator: Option<String>,
+    },
+    /// Create a simulator
+    Create {
+        /// Name of the new simulator
+        name: String,
+
+        /// Device type name or identifier, e.g. "iPhone 15 Pro" (default: the name)
+        #[arg(long)]
+        device_type: Option<String>,
+
+        /// Runtime name, version or identifier, e.g. "iOS 17.2" (default: newest installed)
+        #[arg(long)]
+        runtime: Option<String>,
+    },
+    /// Create the simulators listed under [[ios.simulators]] in config that don't exist yet
+    Provision,
+}
+
+impl IosDeviceCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IosDeviceCommand::Boot { .. } => "boot",
+            IosDeviceCommand::Shutdown { .. } => "shutdown",
+            IosDeviceCommand::Erase { .. } => "erase",
+            IosDeviceCommand::Create { .. } => "create",
+            IosDeviceCommand::Provision => "provision",
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum LogsCommand {
+    /// List recent build logs of all projects
+    List {
+        #[command(flatten)]
+        filter: LogFilterArgs,
+    },
+    /// Show a build log (default: the most recent)
+    Show(ShowLogArgs),
+    /// Find the build errors in the most recent build log
+    Analyze,
+    /// Print a build log as it grows (default: the most recent)
+    Follow {
+        /// Log file name (from 'logs list') or path
+        log: Option<String>,
+
+        /// Only print lines containing this text (case-insensitive)
+        #[arg(long, value_name = "TEXT")]
+        grep: Option<String>,
+
+        /// Only print errors, or errors and warnings
+        #[arg(long, value_enum)]
+        level: Option<LogLevel>,
+
+        /// Exit when the build finishes, with its status
+        #[arg(long)]
+        until_done: bool,
+    },
+}
+
+/// Filters for build logs, matched against their metadata.
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct LogFilterArgs {
+    /// Only logs of this project: its name, its path, or "." for the current directory
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Only logs of this platform
+    #[arg(long, value_enum)]
+    pub platform: Option<Platform>,
+
+    /// Only builds that are running, succeeded or failed
+    #[arg(long, value_enum)]
+    pub status: Option<LogStatus>,
+
+    /// Only builds of this git commit (a prefix is enough)
+    #[arg(long)]
+    pub commit: Option<String>,
+
+    /// Only builds started since a date ("2024-05-01", "2024-05-01 14:30",
+    /// "today", "yesterday") or for a while ("30m", "2h", "3d")
+    #[arg(long, value_name = "WHEN", value_parser = parse_since)]
+    pub since: Option<NaiveDateTime>,
+}
+
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct ShowLogArgs {
+    /// Number from 'logs list' or log file name (default: the most recent match)
+    pub log: Option<String>,
+
+    #[command(flatten)]
+    pub filter: LogFilterArgs,
+
+    /// The most recent failed build (same as --status failed)
+    #[arg(long, conflicts_with = "status")]
+    pub failed: bool,
+
+    /// Only the first N lines
+    #[arg(long, value_name = "N", conflicts_with = "tail")]
+    pub head: Option<usize>,
+
+    /// Only the last N lines
+    #[arg(long, value_name = "N")]
+    pub tail: Option<usize>,
+
+    /// The log as written, without stripping colors and repeated progress lines
+    #[arg(long)]
+    pub raw: bool,
+}
+
+/// A point in time for `--since`: a local date or date and time, "today",
+/// "yesterday", or a duration back from now ("30m", "2h", "3d", "1w").
+pub fn parse_since(value: &str) -> Result<NaiveDateTime, String> {
+    let value = value.trim();
+    let today = Local::now().date_naive();
+    match value {
+        "today" => return Ok(today.and_time(NaiveTime::MIN)),
+        "yesterday" => return Ok((today - chrono::Duration::days(1)).and_time(NaiveTime::MIN)),
+        _ => {}
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(datetime);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN));
+    }
+
+    let invalid = || format!("'{}' is not a date (2024-05-01, 2024-05-01 14:30, today) or duration (30m, 2h, 3d)", value);
+    let unit = value.chars().last().ok_or_else(invalid)?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        'm' => chrono::Duration::minutes(amount),
+        'h' => chrono::Duration::hours(amount),
+        'd' => chrono::Duration::days(amount),
+        'w' => chrono::Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Local::now().naive_local() - duration)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum LogLevel {
+    /// Errors only
+    Error,
+    /// Errors and warnings
+    Warning,
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq)]
+pub enum MetroCommand {
+    /// List running Metro servers with their port and project root
+    Status,
+    /// Reload the JS bundle in connected apps (like pressing `r` in Metro)
+    Reload,
+    /// Open the dev menu in connected apps (like pressing `d` in Metro)
+    #[command(name = "devmenu")]
+    DevMenu,
+    /// Open the debugger for the connected app (like pressing `j` in Metro)
+    Debugger,
+}
+
+impl MetroCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetroCommand::Status => "status",
+            MetroCommand::Reload => "reload",
+            MetroCommand::DevMenu => "devmenu",
+            MetroCommand::Debugger => "debugger",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Platform {
+    Ios,
+    Android,
+}
+
+impl Platform {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Ios => "ios",
+            Platform::Android => "android",
+        }
+    }
+}
+
+impl RunTarget {
+    pub fn platform(&self) -> Platform {
+        match self {
+            RunTarget::Ios(_) => Platform::Ios,
+            RunTarget::Android(_) => Platform::Android,
+        }
+    }
+
+    pub fn build(&self) -> &BuildOptions {
+        match self {
+            RunTarget::Ios(ios) => &ios.build,
+            RunTarget::Android(android) => &android.build,
+        }
+    }
+}
+
+impl CleanTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CleanTarget::Modules => "modules",
+            CleanTarget::Pods => "pods",
+            CleanTarget::Gradle => "gradle",
+            CleanTarget::Metro => "metro",
+        }
+    }
+}
+
+impl Command {
+    /// How the command is spelled on the command line, e.g. "clean pods".
+    pub fn usage(&self) -> String {
+        match self {
+            Command::Run { target } => format!("run {}", target.platform().name()),
+            Command::Clean { targets, pod_install } => {
+                let mut usage = std::iter::once("clean")
+                    .chain(targets.iter().map(|target| target.name()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if *pod_install {
+                    usage.push_str(" --pod-install");
+                }
+                usage
+            }
+            Command::Env { command: EnvCommand::Check } => "env check".to_string(),
+            Command::Devices { command: DevicesCommand::List { platform } } => match platform {
+                Some(platform) => format!("devices list {}", platform.name()),
+                None => "devices list".to_string(),
+            },
+            Command::Devices { command: DevicesCommand::Ios { command } } => format!("devices ios {}", command.name()),
+            Command::Devices { command: DevicesCommand::Android { command } } => format!("devices android {}", command.name()),
+            Command::Logs { command: LogsCommand::List { .. } } => "logs list".to_string(),
+            Command::Logs { command: LogsCommand::Show(_) } => "logs show".to_string(),
+            Command::Logs { command: LogsCommand::Analyze } => "logs analyze".to_string(),
+            Command::Logs { command: LogsCommand::Follow { .. } } => "logs follow".to_string(),
+            Command::Metro { command } => format!("metro {}", command.name()),
+            Command::Explain { id } => match id {
+                Some(id) => format!("explain {}", id),
+                None => "explain".to_string(),
+            },
+            Command::Screenshot { platform, .. } => format!("screenshot {}", platform.name()),
+        }
+    }
+}
+
+impl Args {
+    /// Reject deprecated flags combined with a subcommand (`rn-run -i run android`).
+    /// `--json`, `--dry-run`, `--port` and `--package-manager` may be given on either side.
+    pub fn validate(&self) -> Result<(), clap::Error> {
+        let command = match &self.command {
+            Some(command) => command,
+            None => return Ok(()),
+        };
+
+        let legacy_flags = [
+            (self.ios, "--ios"),
+            (self.android, "--android"),
+            (self.simulator.is_some(), "--simulator"),
+            (self.scheme.is_some(), "--scheme"),
+            (self.variant.is_some(), "--variant"),
+            (self.clean_install, "--clean-install"),
+            (self.upgrade, "--upgrade"),
+            (self.foreground, "--foreground"),
+            (self.check_env, "--check-env"),
+            (self.rn_version, "--rn-version"),
+            (self.list_simulators, "--list-simulators"),
+            (self.list_emulators, "--list-emulators"),
+            (self.print_config, "--print-config"),
+            (self.kill_metro, "--kill-metro"),
+            (self.quit_simulator, "--quit-simulator"),
+            (self.screenshot, "--screenshot"),
+            (self.output.is_some(), "--output"),
+            (self.update, "--update"),
+            (self.logs, "--logs"),
+            (self.show_log, "--show-log"),
+            (self.clean_modules, "--clean-modules"),
+            (self.clean_pods, "--clean-pods"),
+            (self.clean_gradle, "--clean-gradle"),
+            (self.clean_metro, "--clean-metro"),
+            (self.delete_simulators, "--delete-simulators"),
+            (self.delete_emulators, "--delete-emulators"),
+            (self.pod_install, "--pod-install"),
+        ];
+
+        match legacy_flags.iter().find(|(set, _)| *set) {
+            Some((_, flag)) => Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                format!("the argument '{}' cannot be used with the subcommand '{}'", flag, command.usage()),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The subcommand to run. Deprecated flags are mapped onto the subcommand
+    /// they stand for and returned together with the flag that was used.
+    pub fn resolve_command(&self) -> Option<(Command, Option<String>)> {
+        if let Some(command) = &self.command {
+            return Some((command.clone(), None));
+        }
+
+        let build = BuildOptions {
+            clean_install: self.clean_install,
+            upgrade: self.upgrade,
+            foreground: self.foreground,
+            package_manager: self.package_manager,
+        };
+
+        let legacy = if self.screenshot {
+            let platform = if self.android { Platform::Android } else { Platform::Ios };
+            (Command::Screenshot { platform, output: self.output.clone() }, "--screenshot")
+        } else if self.ios {
+            let target = RunTarget::Ios(IosRunArgs {
+                simulator: self.simulator.clone(),
+                device: None,
+                scheme: self.scheme.clone(),
+                build,
+            });
+            (Command::Run { target }, "--ios")
+        } else if self.android {
+            let target = RunTarget::Android(AndroidRunArgs {
+                variant: self.variant.clone(),
+                device: None,
+                all_devices: false,
+                avd: None,
+                build,
+            });
+            (Command::Run { target }, "--android")
+        } else if self.check_env {
+            (Command::Env { command: EnvCommand::Check }, "--check-env")
+        } else if self.list_simulators {
+            (Command::Devices { command: DevicesCommand::List { platform: Some(Platform::Ios) } }, "--list-simulators")
+        } else if self.list_emulators {
+            (Command::Devices { command: DevicesCommand::List { platform: Some(Platform::Android) } }, "--list-emulators")
+        } else if self.logs {
+            (Command::Logs { command: LogsCommand::List { filter: LogFilterArgs::default() } }, "--logs")
+        } else if self.show_log {
+            (Command::Logs { command: LogsCommand::Show(ShowLogArgs::default()) }, "--show-log")
+        } else {
+            let clean_flags = [
+                (self.clean_modules, CleanTarget::Modules, "--clean-modules"),
+                (self.clean_pods, CleanTarget::Pods, "--clean-pods"),
+                (self.clean_gradle, CleanTarget::Gradle, "--clean-gradle"),
+                (self.clean_metro, CleanTarget::Metro, "--clean-metro"),
+            ];
+            let used: Vec<_> = clean_flags.iter().filter(|(set, _, _)| *set).collect();
+            if used.is_empty() && !self.pod_install {
+                return None;
+            }
+
+            // --pod-install is not deprecated on its own, only the --clean-* flags are
+            let command = Command::Clean {
+                targets: used.iter().map(|(_, target, _)| *target).collect(),
+                pod_install: self.pod_install,
+            };
+            let flags = used.iter().map(|(_, _, flag)| *flag).collect::<Vec<_>>().join(" ");
+            return Some((command, Some(flags).filter(|flags| !flags.is_empty())));
+        };
+
+        Some((legacy.0, Some(legacy.1.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_legacy_run_flags_map_to_run_command() {
+        let args = Args::parse_from(["rn-run", "-i", "-s", "iPad Pro", "-c"]);
+        let (command, legacy_flag) = args.resolve_command().unwrap();
+        assert_eq!(legacy_flag.as_deref(), Some("--ios"));
+        match command {
+            Command::Run { target: RunTarget::Ios(ios) } => {
+                assert_eq!(ios.simulator.as_deref(), Some("iPad Pro"));
+                assert!(ios.build.clean_install);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let args = Args::parse_from(["rn-run", "--screenshot", "-a"]);
+        assert_eq!(args.resolve_command().unwrap().0.usage(), "screenshot android");
+    }
+
+    #[test]
+    fn test_legacy_clean_flags_map_to_clean_command() {
+        let args = Args::parse_from(["rn-run", "--pod-install", "--clean-pods", "--clean-metro"]);
+        let (command, legacy_flag) = args.resolve_command().unwrap();
+        assert_eq!(legacy_flag.as_deref(), Some("--clean-pods --clean-metro"));
+        match command {
+            Command::Clean { targets, pod_install } => {
+                assert!(targets.contains(&CleanTarget::Pods) && targets.contains(&CleanTarget::Metro));
+                assert!(pod_install);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subcommands_parse() {
+        let args = Args::parse_from(["rn-run", "clean", "pods", "--json"]);
+        match args.resolve_command().unwrap() {
+            (Command::Clean { targets, pod_install }, None) => {
+                assert_eq!(targets, [CleanTarget::Pods]);
+                assert!(!pod_install);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(args.json);
+
+        let args = Args::parse_from(["rn-run", "run", "android", "--variant", "devDebug", "--foreground"]);
+        let (command, _) = args.resolve_command().unwrap();
+        assert_eq!(command.usage(), "run android");
+        if let Command::Run { target } = command {
+            assert_eq!(target.platform(), Platform::Android);
+            assert!(target.build().foreground);
+            let config = Config::default().apply_run(&target);
+            assert_eq!(config.android.variant.as_deref(), Some("devDebug"));
+        }
+    }
+
+    #[test]
+    fn test_conflicting_flags_are_rejected() {
+        // Flags that used to silently shadow each other
+        assert!(Args::try_parse_from(["rn-run", "--clean-pods", "--kill-metro"]).is_err());
+        assert!(Args::try_parse_from(["rn-run", "-i", "-a"]).is_err());
+        assert!(Args::try_parse_from(["rn-run", "-i", "--clean-metro"]).is_err());
+        assert!(Args::parse_from(["rn-run", "-i", "run", "android"]).validate().is_err());
+        assert!(Args::parse_from(["rn-run", "--json", "run", "android"]).validate().is_ok());
+        assert!(Args::try_parse_from(["rn-run", "run", "android", "--scheme", "App"]).is_err());
+    }
+
+    #[test]
+    fn test_logs_show_options() {
+        let args = Args::parse_from(["rn-run", "logs", "show", "3", "--failed", "--tail", "50", "--raw"]);
+        match args.command {
+            Some(Command::Logs { command: LogsCommand::Show(show) }) => {
+                assert_eq!(show.log.as_deref(), Some("3"));
+                assert!(show.failed && show.raw);
+                assert_eq!(show.tail, Some(50));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(Args::try_parse_from(["rn-run", "logs", "show", "--failed", "--status", "running"]).is_err());
+        assert!(Args::try_parse_from(["rn-run", "logs", "show", "--head", "5", "--tail", "5"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2024-05-01").unwrap().to_string(), "2024-05-01 00:00:00");
+        assert_eq!(parse_since("2024-05-01 14:30").unwrap().to_string(), "2024-05-01 14:30:00");
+        assert!(parse_since("last tuesday").is_err());
+    }
+}
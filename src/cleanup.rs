@@ -0,0 +1,436 @@
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::args::CleanTarget;
+use crate::diagnostics::{avd_dir, list_avd_names};
+use crate::error::{AppError, Result};
+use crate::output::{CleanupResult, CleanupStepResult, RemovedPath};
+use crate::package_manager::PackageManager;
+use crate::runner::CommandRunner;
+
+/// A single cleanup action. Variants are declared in execution order:
+/// caches and build output are removed first, reinstalls run last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CleanupStep {
+    Metro,
+    Modules,
+    Gradle,
+    Pods,
+    ModulesInstall,
+    PodInstall,
+}
+
+impl CleanupStep {
+    pub fn action(&self) -> &'static str {
+        match self {
+            CleanupStep::Metro => "clean-metro",
+            CleanupStep::Modules => "clean-modules",
+            CleanupStep::Gradle => "clean-gradle",
+            CleanupStep::Pods => "clean-pods",
+            CleanupStep::ModulesInstall => "install-modules",
+            CleanupStep::PodInstall => "pod-install",
+        }
+    }
+
+    /// Install steps depend on the cleanup before them and are skipped if it failed.
+    fn is_install(&self) -> bool {
+        matches!(self, CleanupStep::ModulesInstall | CleanupStep::PodInstall)
+    }
+
+    /// Run the step, appending every path it deleted to `removed`.
+    fn run(&self, runner: &dyn CommandRunner, package_manager: PackageManager, removed: &mut Vec<RemovedPath>) -> Result<String> {
+        match self {
+            CleanupStep::Metro => clean_metro(runner, removed),
+            CleanupStep::Modules => clean_modules(runner, package_manager, removed),
+            CleanupStep::Gradle => clean_gradle(runner, removed),
+            CleanupStep::Pods => clean_pods(runner, removed),
+            CleanupStep::ModulesInstall => modules_install(runner, package_manager),
+            CleanupStep::PodInstall => pod_install(runner),
+        }
+    }
+}
+
+impl From<CleanTarget> for CleanupStep {
+    fn from(target: CleanTarget) -> Self {
+        match target {
+            CleanTarget::Modules => CleanupStep::Modules,
+            CleanTarget::Pods => CleanupStep::Pods,
+            CleanTarget::Gradle => CleanupStep::Gradle,
+            CleanTarget::Metro => CleanupStep::Metro,
+        }
+    }
+}
+
+/// Ordered, de-duplicated set of cleanup steps requested in one invocation.
+/// `package_manager` reinstalls node_modules and is named in the messages.
+pub struct CleanupPlan {
+    steps: Vec<CleanupStep>,
+    package_manager: PackageManager,
+}
+
+impl CleanupPlan {
+    pub fn new(steps: impl IntoIterator<Item = CleanupStep>, package_manager: PackageManager) -> Self {
+        let mut steps: Vec<CleanupStep> = steps.into_iter().collect();
+        steps.sort();
+        steps.dedup();
+        CleanupPlan { steps, package_manager }
+    }
+
+    /// Steps for `rn-run clean`. pod install resolves pods from node_modules,
+    /// so after `clean modules` they are reinstalled before it runs.
+    pub fn from_targets(targets: &[CleanTarget], pod_install: bool, package_manager: PackageManager) -> Self {
+        let mut installs = Vec::new();
+        if pod_install {
+            if targets.contains(&CleanTarget::Modules) {
+                installs.push(CleanupStep::ModulesInstall);
+            }
+            installs.push(CleanupStep::PodInstall);
+        }
+        CleanupPlan::new(targets.iter().map(|target| CleanupStep::from(*target)).chain(installs), package_manager)
+    }
+
+    /// JSON command name: the step's own action for a single step, "cleanup" otherwise.
+    pub fn command_name(&self) -> &'static str {
+        match self.steps.as_slice() {
+            [step] => step.action(),
+            _ => "cleanup",
+        }
+    }
+
+    /// Run every step in order. A failing cleanup step does not stop the others,
+    /// but install steps are skipped once anything before them has failed.
+    pub fn execute(&self, runner: &dyn CommandRunner, json: bool) -> CleanupResult {
+        let started = Instant::now();
+        let mut results = Vec::new();
+        let mut failed = false;
+
+        for step in &self.steps {
+            if failed && step.is_install() {
+                results.push(CleanupStepResult {
+                    action: step.action().to_string(),
+                    status: "skipped".to_string(),
+                    error: Some("Skipped because an earlier cleanup step failed".to_string()),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            if !json {
+                println!("\x1b[33m[rn-run]: {}...\x1b[0m", step.action());
+            }
+
+            let step_started = Instant::now();
+            let mut removed = Vec::new();
+            let outcome = step.run(runner, self.package_manager, &mut removed);
+            let duration_ms = step_started.elapsed().as_millis() as u64;
+            // Paths removed before a failure were still freed, so report them either way
+            let bytes_freed = (!step.is_install()).then(|| removed.iter().map(|path| path.bytes).sum());
+
+            results.push(match outcome {
+                Ok(message) => CleanupStepResult {
+                    action: step.action().to_string(),
+                    status: "ok".to_string(),
+                    duration_ms,
+                    message: Some(message),
+                    removed,
+                    bytes_freed,
+                    ..Default::default()
+                },
+                Err(e) => {
+                    failed = true;
+                    CleanupStepResult {
+                        action: step.action().to_string(),
+                        status: "failed".to_string(),
+                        duration_ms,
+                        error: Some(e.to_string()),
+                        fix: e.fix(),
+                        removed,
+                        bytes_freed,
+                        ..Default::default()
+                    }
+                }
+            });
+        }
+
+        let failures = results.iter().filter(|result| result.status != "ok").count();
+        let message = if failures == 0 {
+            format!("{} cleanup step(s) completed", results.len())
+        } else {
+            format!("{} of {} cleanup step(s) did not complete", failures, results.len())
+        };
+
+        CleanupResult {
+            success: failures == 0,
+            bytes_freed: results.iter().filter_map(|result| result.bytes_freed).sum(),
+            steps: results,
+            duration_ms: started.elapsed().as_millis() as u64,
+            message,
+        }
+    }
+}
+
+/// Remove `path` natively, recording it in `removed` if anything was there.
+fn remove(runner: &dyn CommandRunner, path: &Path, removed: &mut Vec<RemovedPath>) -> Result<()> {
+    if let Some(bytes) = runner.remove_path(path)? {
+        removed.push(RemovedPath {
+            path: path.to_string_lossy().to_string(),
+            bytes,
+        });
+    }
+    Ok(())
+}
+
+fn clean_modules(runner: &dyn CommandRunner, package_manager: PackageManager, removed: &mut Vec<RemovedPath>) -> Result<String> {
+    remove(runner, Path::new("node_modules"), removed)?;
+    Ok(format!("node_modules deleted. Run {} to reinstall.", package_manager.install()))
+}
+
+fn clean_pods(runner: &dyn CommandRunner, removed: &mut Vec<RemovedPath>) -> Result<String> {
+    remove(runner, Path::new("ios/Pods"), removed)?;
+    remove(runner, Path::new("ios/Podfile.lock"), removed)?;
+    remove(runner, Path::new("ios/build"), removed)?;
+    Ok("Cleaned ios/Pods, ios/Podfile.lock, ios/build. Run 'rn-run clean --pod-install' to reinstall.".to_string())
+}
+
+fn clean_gradle(runner: &dyn CommandRunner, removed: &mut Vec<RemovedPath>) -> Result<String> {
+    // Stop Gradle daemons first
+    if Path::new("android/gradlew").exists() {
+        let _ = runner.status("./android/gradlew", &["--stop"]);
+    }
+
+    remove(runner, Path::new("android/build"), removed)?;
+    remove(runner, Path::new("android/app/build"), removed)?;
+    remove(runner, Path::new("android/.gradle"), removed)?;
+
+    // Clean version-specific global Gradle cache
+    let mut global_cache_msg = String::new();
+    if let Some(version) = gradle_wrapper_version(Path::new("android/gradle/wrapper/gradle-wrapper.properties")) {
+        if let Ok(home) = std::env::var("HOME") {
+            let cache_path = PathBuf::from(&home).join(".gradle").join("caches").join(&version);
+            if cache_path.exists() {
+                remove(runner, &cache_path, removed)?;
+                global_cache_msg = format!(", ~/.gradle/caches/{}", version);
+            }
+        }
+    }
+
+    Ok(format!("Stopped Gradle daemons. Cleaned android/build, android/app/build, android/.gradle{}", global_cache_msg))
+}
+
+/// Gradle version from the wrapper's distributionUrl, e.g. gradle-8.8-all.zip or gradle-8.8-bin.zip
+fn gradle_wrapper_version(wrapper_props: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(wrapper_props).ok()?;
+    let line = contents.lines().find(|l| l.starts_with("distributionUrl"))?;
+    let after = &line[line.find("gradle-")? + 7..];
+    let end = after.find('-')?;
+    Some(after[..end].to_string())
+}
+
+fn clean_metro(runner: &dyn CommandRunner, removed: &mut Vec<RemovedPath>) -> Result<String> {
+    let caches = metro_cache_paths(&std::env::temp_dir());
+    for path in &caches {
+        remove(runner, path, removed)?;
+    }
+
+    if caches.is_empty() {
+        Ok("Metro cache already empty".to_string())
+    } else {
+        Ok(format!("Metro cache cleared ({} entries)", caches.len()))
+    }
+}
+
+/// Metro and haste-map cache entries in the temp directory ($TMPDIR).
+fn metro_cache_paths(tmpdir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(tmpdir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    name.starts_with("metro-") || name.starts_with("haste-map-")
+                })
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+fn modules_install(runner: &dyn CommandRunner, package_manager: PackageManager) -> Result<String> {
+    let command = package_manager.install();
+    let installed = runner
+        .status(package_manager.name(), &["install"])
+        .map_err(|_| AppError::CommandFailed(command.clone()))?;
+
+    if !installed {
+        return Err(AppError::CommandFailed(format!("{} failed", command)));
+    }
+    Ok(format!("{} completed successfully", command))
+}
+
+fn pod_install(runner: &dyn CommandRunner) -> Result<String> {
+    let installed = runner
+        .status("sh", &["-c", "cd ios && pod install"])
+        .map_err(|_| AppError::CommandFailed("pod install".to_string()))?;
+
+    if !installed {
+        return Err(AppError::CommandFailed("pod install failed".to_string()));
+    }
+    Ok("pod install completed successfully".to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SIMULATOR / EMULATOR DELETION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Delete every iOS simulator.
+pub fn delete_simulators(runner: &dyn CommandRunner) -> Result<()> {
+    let deleted = runner
+        .status("xcrun", &["simctl", "delete", "all"])
+        .map_err(|_| AppError::CommandFailed("xcrun simctl delete all".to_string()))?;
+
+    if !deleted {
+        return Err(AppError::CommandFailed("xcrun simctl delete all failed".to_string()));
+    }
+    Ok(())
+}
+
+/// AVDs removed by `delete_emulators`. Deletion carries on past an AVD that
+/// can't be removed, so `error` can come with some AVDs already deleted.
+pub struct EmulatorDeletion {
+    pub deleted: Vec<String>,
+    pub failed: Vec<String>,
+    /// The first failure
+    pub error: Option<AppError>,
+}
+
+/// Shut down running emulators and delete every AVD in ~/.android/avd (or `$ANDROID_AVD_HOME`).
+/// The removed files are appended to `removed`, also when some AVDs could not be deleted.
+pub fn delete_emulators(runner: &dyn CommandRunner, removed: &mut Vec<RemovedPath>) -> EmulatorDeletion {
+    // Kill any running emulators first by finding all emulator devices
+    let mut killed_any = false;
+    if let Ok(output) = runner.output("adb", &["devices"]) {
+        let devices = output.stdout_str();
+        for line in devices.lines() {
+            if line.starts_with("emulator-") {
+                if let Some(device_id) = line.split_whitespace().next() {
+                    let _ = runner.output("adb", &["-s", device_id, "emu", "kill"]);
+                    killed_any = true;
+                }
+            }
+        }
+    }
+    // Wait for emulators to shut down
+    if killed_any && !runner.is_dry_run() {
+        std::thread::sleep(std::time::Duration::from_secs(2));
+    }
+
+    let avd_dir = avd_dir();
+
+    let mut deletion = EmulatorDeletion { deleted: Vec::new(), failed: Vec::new(), error: None };
+
+    for name in list_avd_names(runner) {
+        // The .avd directory holds the disk images, the .ini file points the emulator at it
+        let result = remove(runner, &avd_dir.join(format!("{}.avd", name)), removed)
+            .and_then(|_| remove(runner, &avd_dir.join(format!("{}.ini", name)), removed));
+
+        match result {
+            Ok(()) => deletion.deleted.push(name),
+            Err(e) => {
+                deletion.failed.push(name);
+                deletion.error.get_or_insert(e);
+            }
+        }
+    }
+
+    deletion
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+    use crate::test_support::{EnvGuard, TempDir};
+
+    #[test]
+    fn test_plan_orders_and_reports_steps() {
+        let plan = CleanupPlan::from_targets(&[CleanTarget::Pods, CleanTarget::Metro], true, PackageManager::Npm);
+        assert_eq!(plan.command_name(), "cleanup");
+
+        let runner = FakeRunner::new()
+            .allow_unscripted()
+            .on("remove ios/Pods", "2048")
+            .on("remove ios/build", "512")
+            .on_fail("sh -c cd ios && pod install", "[!] No `Podfile' found");
+        let result = plan.execute(&runner, true);
+        let actions: Vec<&str> = result.steps.iter().map(|step| step.action.as_str()).collect();
+        assert_eq!(actions, ["clean-metro", "clean-pods", "pod-install"]);
+        assert_eq!(result.steps[1].status, "ok");
+        assert_eq!(result.steps[1].removed.len(), 2);
+        assert_eq!(result.steps[1].bytes_freed, Some(2560));
+        assert_eq!(result.steps[2].status, "failed");
+        assert_eq!(result.steps[2].bytes_freed, None);
+        assert!(!result.success);
+        assert!(!runner.calls().iter().any(|call| call.starts_with("rm ")));
+    }
+
+    #[test]
+    fn test_installs_are_skipped_after_a_failed_step() {
+        // Instead of run on a half-cleaned tree
+        let runner = FakeRunner::new().on_fail("remove ios/Pods", "");
+        let result = CleanupPlan::new([CleanupStep::PodInstall, CleanupStep::Pods], PackageManager::Npm).execute(&runner, true);
+        assert_eq!(result.steps[0].status, "failed");
+        assert_eq!(result.steps[0].error.as_deref(), Some("Permission denied removing ios/Pods"));
+        assert!(result.steps[0].fix.as_deref().unwrap().contains("chown"));
+        assert_eq!(result.steps[1].status, "skipped");
+    }
+
+    #[test]
+    fn test_single_step_plan_is_named_after_it() {
+        assert_eq!(CleanupPlan::new([CleanupStep::Pods, CleanupStep::Pods], PackageManager::Npm).command_name(), "clean-pods");
+    }
+
+    #[test]
+    fn test_modules_are_reinstalled_before_pod_install() {
+        let plan = CleanupPlan::from_targets(&[CleanTarget::Modules], true, PackageManager::Yarn);
+        let runner = FakeRunner::new()
+            .on("remove node_modules", "4096")
+            .on("yarn install", "")
+            .on("sh -c cd ios && pod install", "");
+        let result = plan.execute(&runner, true);
+
+        let actions: Vec<&str> = result.steps.iter().map(|step| step.action.as_str()).collect();
+        assert_eq!(actions, ["clean-modules", "install-modules", "pod-install"]);
+        assert_eq!(runner.calls(), ["remove node_modules", "yarn install", "sh -c cd ios && pod install"]);
+        assert_eq!(result.steps[0].message.as_deref(), Some("node_modules deleted. Run yarn install to reinstall."));
+        assert!(result.success);
+
+        // Without --pod-install node_modules is only deleted
+        let plan = CleanupPlan::from_targets(&[CleanTarget::Modules], false, PackageManager::Yarn);
+        assert_eq!(plan.command_name(), "clean-modules");
+    }
+
+    #[test]
+    fn test_emulator_deletion_reports_deleted_avds_and_the_failure() {
+        let avd_home = TempDir::new("avd-home");
+        let mut env = EnvGuard::lock();
+        env.set("ANDROID_AVD_HOME", avd_home.path());
+        let avd = |name: &str| avd_home.join(name).to_string_lossy().to_string();
+
+        let runner = FakeRunner::new()
+            .on("adb devices", "List of devices attached\n")
+            .on("emulator -list-avds", "Pixel_7\nPixel_Tablet\nWear_OS\n")
+            .on(&format!("remove {}", avd("Pixel_7.avd")), "4096")
+            .on(&format!("remove {}", avd("Pixel_7.ini")), "100")
+            .on_fail(&format!("remove {}", avd("Pixel_Tablet.avd")), "")
+            .on(&format!("remove {}", avd("Wear_OS.avd")), "2048")
+            .on(&format!("remove {}", avd("Wear_OS.ini")), "100");
+        let mut removed = Vec::new();
+        let deletion = delete_emulators(&runner, &mut removed);
+
+        assert_eq!(deletion.deleted, ["Pixel_7", "Wear_OS"]);
+        assert_eq!(deletion.failed, ["Pixel_Tablet"]);
+        assert!(matches!(deletion.error, Some(AppError::PermissionDenied(_))));
+        assert_eq!(removed.iter().map(|path| path.bytes).sum::<u64>(), 6344);
+    }
+}
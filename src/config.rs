@@ -0,0 +1,327 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::args::{Args, RunTarget};
+use crate::error::{AppError, Result};
+use crate::package_manager::PackageManager;
+
+const PROJECT_CONFIG: &str = ".rn-run.toml";
+pub const DEFAULT_SIMULATOR: &str = "iPhone 15";
+pub const DEFAULT_METRO_PORT: u16 = 8081;
+pub const DEFAULT_MAX_LOGS: usize = 10;
+
+/// Per-project defaults loaded from `.rn-run.toml` in the project root,
+/// layered over the user config in `~/.config/rn-run/config.toml`.
+/// CLI flags override both (see `apply_args`).
+///
+/// ```toml
+/// package_manager = "yarn"
+///
+/// [ios]
+/// simulator = "iPhone 15 Pro"
+/// device = "QA iPhone 13"
+/// scheme = "MyAppDev"
+///
+/// [[ios.simulators]]
+/// name = "iPhone 15 Pro"
+/// runtime = "iOS 17.2"
+///
+/// [[ios.simulators]]
+/// name = "iPad (small)"
+/// device_type = "iPad mini (6th generation)"
+///
+/// [android]
+/// device = "R58M12345"
+/// all_devices = false
+/// avd = "Pixel_7_API_34"
+/// variant = "devDebug"
+///
+/// [metro]
+/// port = 8082
+///
+/// [logs]
+/// max_logs = 20
+///
+/// [hooks]
+/// pre_build = "npm run codegen"
+/// post_build = "say done"
+/// ```
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<PackageManager>,
+    pub ios: IosConfig,
+    pub android: AndroidConfig,
+    pub metro: MetroConfig,
+    pub logs: LogsConfig,
+    pub hooks: HooksConfig,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IosConfig {
+    /// Simulator name passed to run-ios --simulator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulator: Option<String>,
+    /// Physical device name or UDID; run-ios targets it instead of a simulator
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Xcode scheme passed to run-ios --scheme
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    /// Standard simulator set created by `devices ios provision`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub simulators: Vec<SimulatorSpec>,
+}
+
+/// A simulator to create with `xcrun simctl create`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulatorSpec {
+    pub name: String,
+    /// Device type name or identifier; defaults to `name`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Runtime name, version or identifier; defaults to the newest iOS runtime
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AndroidConfig {
+    /// Device serial or model passed to run-android --deviceId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Install on every connected device instead of one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_devices: Option<bool>,
+    /// AVD to start when no device is connected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avd: Option<String>,
+    /// Build variant passed to run-android --mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetroConfig {
+    /// Port Metro listens on; passed to Metro, run-ios/run-android and adb reverse
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogsConfig {
+    /// Number of build logs to keep per project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_logs: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Shell command run before the native build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_build: Option<String>,
+    /// Shell command run after a successful native build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_build: Option<String>,
+}
+
+impl Config {
+    /// Load the user config and the project config from `project_dir`.
+    /// Returns the merged config and the files it was read from.
+    pub fn load(project_dir: &Path) -> Result<(Config, Vec<String>)> {
+        let mut config = Config::default();
+        let mut sources = Vec::new();
+
+        let paths = [user_config_path(), Some(project_dir.join(PROJECT_CONFIG))];
+        for path in paths.into_iter().flatten() {
+            if let Some(file_config) = Config::from_file(&path)? {
+                config = config.merge(file_config);
+                sources.push(path.to_string_lossy().to_string());
+            }
+        }
+
+        Ok((config, sources))
+    }
+
+    fn from_file(path: &Path) -> Result<Option<Config>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| AppError::Config(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Overlay `other` on top of this config; values set in `other` win.
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            package_manager: other.package_manager.or(self.package_manager),
+            ios: IosConfig {
+                simulator: other.ios.simulator.or(self.ios.simulator),
+                device: other.ios.device.or(self.ios.device),
+                scheme: other.ios.scheme.or(self.ios.scheme),
+                // The set is replaced as a whole, not merged entry by entry
+                simulators: if other.ios.simulators.is_empty() { self.ios.simulators } else { other.ios.simulators },
+            },
+            android: AndroidConfig {
+                device: other.android.device.or(self.android.device),
+                all_devices: other.android.all_devices.or(self.android.all_devices),
+                avd: other.android.avd.or(self.android.avd),
+                variant: other.android.variant.or(self.android.variant),
+            },
+            metro: MetroConfig {
+                port: other.metro.port.or(self.metro.port),
+            },
+            logs: LogsConfig {
+                max_logs: other.logs.max_logs.or(self.logs.max_logs),
+            },
+            hooks: HooksConfig {
+                pre_build: other.hooks.pre_build.or(self.hooks.pre_build),
+                post_build: other.hooks.post_build.or(self.hooks.post_build),
+            },
+        }
+    }
+
+    pub fn simulator(&self) -> &str {
+        self.ios.simulator.as_deref().unwrap_or(DEFAULT_SIMULATOR)
+    }
+
+    pub fn all_devices(&self) -> bool {
+        self.android.all_devices.unwrap_or(false)
+    }
+
+    pub fn metro_port(&self) -> u16 {
+        self.metro.port.unwrap_or(DEFAULT_METRO_PORT)
+    }
+
+    pub fn max_logs(&self) -> usize {
+        self.logs.max_logs.unwrap_or(DEFAULT_MAX_LOGS)
+    }
+
+    /// Apply CLI flags, which take precedence over config files.
+    pub fn apply_args(mut self, args: &Args) -> Config {
+        if args.package_manager.is_some() {
+            self.package_manager = args.package_manager;
+        }
+        if args.simulator.is_some() {
+            self.ios.simulator = args.simulator.clone();
+        }
+        if args.scheme.is_some() {
+            self.ios.scheme = args.scheme.clone();
+        }
+        if args.variant.is_some() {
+            self.android.variant = args.variant.clone();
+        }
+        if args.port.is_some() {
+            self.metro.port = args.port;
+        }
+        self
+    }
+
+    /// Apply the options given to `run ios` / `run android`.
+    pub fn apply_run(mut self, target: &RunTarget) -> Config {
+        if target.build().package_manager.is_some() {
+            self.package_manager = target.build().package_manager;
+        }
+        match target {
+            RunTarget::Ios(ios) => {
+                // Asking for a simulator or a device overrides a configured choice of the other
+                if ios.simulator.is_some() {
+                    self.ios.simulator = ios.simulator.clone();
+                    self.ios.device = None;
+                }
+                if ios.device.is_some() {
+                    self.ios.device = ios.device.clone();
+                    self.ios.simulator = None;
+                }
+                if ios.scheme.is_some() {
+                    self.ios.scheme = ios.scheme.clone();
+                }
+            }
+            RunTarget::Android(android) => {
+                if android.variant.is_some() {
+                    self.android.variant = android.variant.clone();
+                }
+                if android.avd.is_some() {
+                    self.android.avd = android.avd.clone();
+                }
+                if android.device.is_some() {
+                    self.android.device = android.device.clone();
+                    self.android.all_devices = None;
+                }
+                if android.all_devices {
+                    self.android.all_devices = Some(true);
+                    self.android.device = None;
+                }
+            }
+        }
+        self
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rn-run/config.toml`, defaulting to `~/.config/rn-run/config.toml`.
+pub fn user_config_path() -> Option<PathBuf> {
+    let config_home = env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok()?;
+    Some(config_home.join("rn-run").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_config_merge_and_cli_overrides() {
+        let user: Config = toml::from_str(r#"
+            package_manager = "npm"
+            [ios]
+            simulator = "iPhone 15"
+            [logs]
+            max_logs = 5
+        "#).unwrap();
+        let project: Config = toml::from_str(r#"
+            package_manager = "yarn"
+            [ios]
+            simulator = "iPhone 15 Pro"
+            scheme = "MyAppDev"
+            [metro]
+            port = 8082
+        "#).unwrap();
+
+        let merged = user.merge(project);
+        assert_eq!(merged.package_manager, Some(PackageManager::Yarn));
+        assert_eq!(merged.simulator(), "iPhone 15 Pro");
+        assert_eq!(merged.max_logs(), 5);
+        assert_eq!(merged.metro_port(), 8082);
+
+        let args = Args::parse_from(["rn-run", "-i", "-s", "iPad Pro"]);
+        let effective = merged.apply_args(&args);
+        assert_eq!(effective.simulator(), "iPad Pro");
+        assert_eq!(effective.ios.scheme.as_deref(), Some("MyAppDev"));
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_keys() {
+        assert!(toml::from_str::<Config>("[ios]\nsimulatr = \"typo\"").is_err());
+    }
+
+    #[test]
+    fn test_metro_port_flag_overrides_config() {
+        // --port flows into the config used for the project's own Metro
+        let args = Args::parse_from(["rn-run", "metro", "status", "--port", "8090"]);
+        assert_eq!(Config::default().apply_args(&args).metro_port(), 8090);
+    }
+}
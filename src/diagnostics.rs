@@ -1,55 +1,59 @@
-use std::process::Command;
-use crate::output::{EnvCheck, EnvCheckResult, SimulatorListResult, Simulator, EmulatorListResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::emulator::avd_name;
+use crate::runner::CommandRunner;
+use crate::output::{AndroidDevice, Avd, EnvCheck, EnvCheckResult, IosDevice, SimulatorListResult, Simulator, EmulatorListResult};
 use serde::Deserialize;
 
 /// Check all development environment dependencies.
 /// Returns structured data about each tool's availability and version.
-pub fn check_environment() -> EnvCheckResult {
+pub fn check_environment(runner: &dyn CommandRunner) -> EnvCheckResult {
     let mut checks = Vec::new();
     let mut has_errors = false;
     let mut has_warnings = false;
 
     // macOS version
-    checks.push(check_macos());
+    checks.push(check_macos(runner));
 
     // Command Line Tools
-    checks.push(check_clt());
+    checks.push(check_clt(runner));
 
     // Node.js
-    checks.push(check_node());
+    checks.push(check_node(runner));
 
     // npm
-    checks.push(check_npm());
+    checks.push(check_npm(runner));
 
     // Yarn
-    checks.push(check_yarn());
+    checks.push(check_yarn(runner));
 
     // Watchman
-    checks.push(check_watchman());
+    checks.push(check_watchman(runner));
 
     // Xcode (iOS)
-    checks.push(check_xcode());
+    checks.push(check_xcode(runner));
 
     // CocoaPods (iOS)
-    checks.push(check_cocoapods());
+    checks.push(check_cocoapods(runner));
 
     // Ruby (iOS - needed for CocoaPods)
-    checks.push(check_ruby());
+    checks.push(check_ruby(runner));
 
     // Bundler (iOS - for Gemfile management)
-    checks.push(check_bundler());
+    checks.push(check_bundler(runner));
 
     // Swift (iOS)
-    checks.push(check_swift());
+    checks.push(check_swift(runner));
 
     // Android SDK
     checks.push(check_android_sdk());
 
     // Java (Android)
-    checks.push(check_java());
+    checks.push(check_java(runner));
 
     // Gradle (Android)
-    checks.push(check_gradle());
+    checks.push(check_gradle(runner));
 
     // Android Gradle Plugin (Android)
     checks.push(check_android_gradle_plugin());
@@ -98,8 +102,8 @@ pub fn check_environment() -> EnvCheckResult {
     }
 }
 
-fn check_macos() -> EnvCheck {
-    match get_command_version("sw_vers", &["-productVersion"]) {
+fn check_macos(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "sw_vers", &["-productVersion"]) {
         Some(version) => EnvCheck {
             name: "macos".to_string(),
             ok: true,
@@ -119,15 +123,13 @@ fn check_macos() -> EnvCheck {
     }
 }
 
-fn check_clt() -> EnvCheck {
+fn check_clt(runner: &dyn CommandRunner) -> EnvCheck {
     // Check Command Line Tools version
-    let output = Command::new("pkgutil")
-        .args(["--pkg-info=com.apple.pkg.CLTools_Executables"])
-        .output();
+    let output = runner.output("pkgutil", &["--pkg-info=com.apple.pkg.CLTools_Executables"]);
 
     match output {
-        Ok(out) if out.status.success() => {
-            let stdout = String::from_utf8_lossy(&out.stdout);
+        Ok(out) if out.success => {
+            let stdout = out.stdout_str();
             let version = stdout.lines()
                 .find(|line| line.starts_with("version:"))
                 .map(|line| line.replace("version:", "").trim().to_string())
@@ -153,8 +155,8 @@ fn check_clt() -> EnvCheck {
     }
 }
 
-fn check_node() -> EnvCheck {
-    match get_command_version("node", &["--version"]) {
+fn check_node(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "node", &["--version"]) {
         Some(version) => EnvCheck {
             name: "node".to_string(),
             ok: true,
@@ -174,8 +176,8 @@ fn check_node() -> EnvCheck {
     }
 }
 
-fn check_npm() -> EnvCheck {
-    match get_command_version("npm", &["--version"]) {
+fn check_npm(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "npm", &["--version"]) {
         Some(version) => EnvCheck {
             name: "npm".to_string(),
             ok: true,
@@ -195,8 +197,8 @@ fn check_npm() -> EnvCheck {
     }
 }
 
-fn check_yarn() -> EnvCheck {
-    match get_command_version("yarn", &["--version"]) {
+fn check_yarn(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "yarn", &["--version"]) {
         Some(version) => EnvCheck {
             name: "yarn".to_string(),
             ok: true,
@@ -216,8 +218,8 @@ fn check_yarn() -> EnvCheck {
     }
 }
 
-fn check_watchman() -> EnvCheck {
-    match get_command_version("watchman", &["--version"]) {
+fn check_watchman(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "watchman", &["--version"]) {
         Some(version) => EnvCheck {
             name: "watchman".to_string(),
             ok: true,
@@ -237,16 +239,14 @@ fn check_watchman() -> EnvCheck {
     }
 }
 
-fn check_xcode() -> EnvCheck {
+fn check_xcode(runner: &dyn CommandRunner) -> EnvCheck {
     // Check xcodebuild version
-    match get_command_version("xcodebuild", &["-version"]) {
+    match get_command_version(runner, "xcodebuild", &["-version"]) {
         Some(version) => {
             // Also check if command line tools are accepted
-            let license_check = Command::new("xcodebuild")
-                .arg("-checkFirstLaunchStatus")
-                .output();
+            let license_check = runner.output("xcodebuild", &["-checkFirstLaunchStatus"]);
 
-            let license_ok = license_check.map(|o| o.status.success()).unwrap_or(false);
+            let license_ok = license_check.map(|o| o.success).unwrap_or(false);
 
             if license_ok {
                 EnvCheck {
@@ -279,8 +279,8 @@ fn check_xcode() -> EnvCheck {
     }
 }
 
-fn check_cocoapods() -> EnvCheck {
-    match get_command_version("pod", &["--version"]) {
+fn check_cocoapods(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "pod", &["--version"]) {
         Some(version) => EnvCheck {
             name: "cocoapods".to_string(),
             ok: true,
@@ -300,8 +300,8 @@ fn check_cocoapods() -> EnvCheck {
     }
 }
 
-fn check_ruby() -> EnvCheck {
-    match get_command_version("ruby", &["--version"]) {
+fn check_ruby(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "ruby", &["--version"]) {
         Some(version) => EnvCheck {
             name: "ruby".to_string(),
             ok: true,
@@ -360,8 +360,8 @@ fn check_android_sdk() -> EnvCheck {
     }
 }
 
-fn check_java() -> EnvCheck {
-    match get_command_version("java", &["-version"]) {
+fn check_java(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "java", &["-version"]) {
         Some(version) => EnvCheck {
             name: "java".to_string(),
             ok: true,
@@ -381,8 +381,8 @@ fn check_java() -> EnvCheck {
     }
 }
 
-fn check_bundler() -> EnvCheck {
-    match get_command_version("bundler", &["--version"]) {
+fn check_bundler(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "bundler", &["--version"]) {
         Some(version) => EnvCheck {
             name: "bundler".to_string(),
             ok: true,
@@ -402,8 +402,8 @@ fn check_bundler() -> EnvCheck {
     }
 }
 
-fn check_swift() -> EnvCheck {
-    match get_command_version("swift", &["--version"]) {
+fn check_swift(runner: &dyn CommandRunner) -> EnvCheck {
+    match get_command_version(runner, "swift", &["--version"]) {
         Some(version) => {
             // Extract just the Swift version from output like "swift-driver version: 1.87.1 Apple Swift version 5.9.2..."
             let swift_version = version.lines()
@@ -436,18 +436,16 @@ fn check_swift() -> EnvCheck {
     }
 }
 
-fn check_gradle() -> EnvCheck {
+fn check_gradle(runner: &dyn CommandRunner) -> EnvCheck {
     // Check if we're in a React Native project with android directory
     let has_android_dir = std::path::Path::new("android").exists();
 
     // Try to get Gradle version from the project's gradlew wrapper first
-    let gradlew_output = Command::new("./android/gradlew")
-        .args(["--version"])
-        .output();
+    let gradlew_output = runner.output("./android/gradlew", &["--version"]);
 
     if let Ok(out) = gradlew_output {
-        if out.status.success() {
-            let stdout = String::from_utf8_lossy(&out.stdout);
+        if out.success {
+            let stdout = out.stdout_str();
             let version = stdout.lines()
                 .find(|line| line.starts_with("Gradle "))
                 .map(|line| line.to_string())
@@ -465,7 +463,7 @@ fn check_gradle() -> EnvCheck {
     }
 
     // Fall back to system gradle
-    match get_command_version("gradle", &["--version"]) {
+    match get_command_version(runner, "gradle", &["--version"]) {
         Some(version) => {
             let version_line = version.lines()
                 .find(|line| line.starts_with("Gradle "))
@@ -598,7 +596,7 @@ fn parse_agp_from_gradle(content: &str) -> Option<String> {
         if line.contains("com.android.tools.build:gradle:") {
             return line.split("gradle:")
                 .nth(1)
-                .and_then(|s| s.split(|c| c == '"' || c == '\'' || c == ')').next())
+                .and_then(|s| s.split(['"', '\'', ')']).next())
                 .map(|s| s.to_string());
         }
 
@@ -613,7 +611,7 @@ fn parse_agp_from_gradle(content: &str) -> Option<String> {
 
 fn extract_quoted_version(line: &str) -> Option<String> {
     // Extract version from: key = "1.2.3" or key = '1.2.3'
-    line.split(|c| c == '"' || c == '\'')
+    line.split(['"', '\''])
         .find(|s| s.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false))
         .map(|s| s.to_string())
 }
@@ -622,7 +620,7 @@ fn extract_version_after_keyword(line: &str, keyword: &str) -> Option<String> {
     line.split(keyword)
         .nth(1)
         .and_then(|s| {
-            s.split(|c| c == '"' || c == '\'')
+            s.split(['"', '\''])
                 .find(|s| s.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false))
         })
         .map(|s| s.to_string())
@@ -731,7 +729,7 @@ fn parse_kotlin_from_gradle(content: &str) -> Option<String> {
         if line.contains("kotlin-gradle-plugin:") {
             return line.split("kotlin-gradle-plugin:")
                 .nth(1)
-                .and_then(|s| s.split(|c| c == '"' || c == '\'' || c == ')').next())
+                .and_then(|s| s.split(['"', '\'', ')']).next())
                 .map(|s| s.to_string());
         }
     }
@@ -864,17 +862,16 @@ fn check_build_tools() -> EnvCheck {
     }
 }
 
-fn get_command_version(cmd: &str, args: &[&str]) -> Option<String> {
-    Command::new(cmd)
-        .args(args)
-        .output()
+fn get_command_version(runner: &dyn CommandRunner, cmd: &str, args: &[&str]) -> Option<String> {
+    runner
+        .output(cmd, args)
         .ok()
-        .filter(|output| output.status.success())
+        .filter(|output| output.success)
         .map(|output| {
             // Try stdout first, then stderr (java -version outputs to stderr)
-            let out = String::from_utf8_lossy(&output.stdout);
+            let out = output.stdout_str();
             if out.trim().is_empty() {
-                String::from_utf8_lossy(&output.stderr).trim().lines().next().unwrap_or("").to_string()
+                output.stderr_str().trim().lines().next().unwrap_or("").to_string()
             } else {
                 out.trim().lines().next().unwrap_or("").to_string()
             }
@@ -882,20 +879,166 @@ fn get_command_version(cmd: &str, args: &[&str]) -> Option<String> {
 }
 
 /// List available iOS simulators using xcrun simctl.
-pub fn list_simulators() -> SimulatorListResult {
-    let output = Command::new("xcrun")
-        .args(["simctl", "list", "devices", "--json"])
-        .output();
+pub fn list_simulators(runner: &dyn CommandRunner) -> SimulatorListResult {
+    let output = runner.output("xcrun", &["simctl", "list", "devices", "--json"]);
 
     match output {
-        Ok(out) if out.status.success() => {
-            let json_str = String::from_utf8_lossy(&out.stdout);
+        Ok(out) if out.success => {
+            let json_str = out.stdout_str();
             parse_simulators(&json_str)
         },
         _ => SimulatorListResult { simulators: vec![] },
     }
 }
 
+/// Physical iOS devices known to Xcode, from `devicectl` (Xcode 15+) or, on
+/// older Xcode, `xctrace`.
+pub fn list_ios_devices(runner: &dyn CommandRunner) -> Vec<IosDevice> {
+    let devicectl = runner.output("xcrun", &["devicectl", "list", "devices", "--quiet", "--json-output", "/dev/stdout"]);
+    if let Ok(output) = devicectl {
+        if output.success {
+            if let Some(devices) = parse_devicectl_devices(&output.stdout_str()) {
+                return devices;
+            }
+        }
+    }
+
+    match runner.output("xcrun", &["xctrace", "list", "devices"]) {
+        Ok(output) if output.success => parse_xctrace_devices(&output.stdout_str()),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Deserialize)]
+struct DevicectlOutput {
+    result: DevicectlResult,
+}
+
+#[derive(Deserialize)]
+struct DevicectlResult {
+    devices: Vec<DevicectlDevice>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevicectlDevice {
+    device_properties: DevicectlDeviceProperties,
+    hardware_properties: DevicectlHardwareProperties,
+    connection_properties: DevicectlConnectionProperties,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevicectlDeviceProperties {
+    name: String,
+    os_version_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevicectlHardwareProperties {
+    udid: String,
+    marketing_name: Option<String>,
+    platform: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevicectlConnectionProperties {
+    transport_type: Option<String>,
+    tunnel_state: Option<String>,
+}
+
+/// iOS devices in `xcrun devicectl list devices --json-output` output. Paired
+/// devices that are out of reach have a tunnel state of "unavailable" and are
+/// reported as offline. None if the output isn't devicectl JSON.
+pub fn parse_devicectl_devices(json_str: &str) -> Option<Vec<IosDevice>> {
+    let output: DevicectlOutput = serde_json::from_str(json_str).ok()?;
+
+    let devices = output
+        .result
+        .devices
+        .into_iter()
+        .filter(|device| device.hardware_properties.platform.as_deref().is_none_or(|platform| platform == "iOS"))
+        .map(|device| {
+            let connection = device.connection_properties;
+            let transport = match connection.transport_type.as_deref() {
+                Some("wired") => Some("usb".to_string()),
+                Some("localNetwork") => Some("wifi".to_string()),
+                _ => None,
+            };
+            let state = match (connection.tunnel_state.as_deref(), &transport) {
+                (Some("unavailable"), _) | (None, None) => "offline",
+                _ => "connected",
+            };
+            IosDevice {
+                name: device.device_properties.name,
+                udid: device.hardware_properties.udid,
+                model: device.hardware_properties.marketing_name,
+                os_version: device.device_properties.os_version_number,
+                transport,
+                state: state.to_string(),
+            }
+        })
+        .collect();
+    Some(devices)
+}
+
+/// iOS devices in `xcrun xctrace list devices` output:
+///
+/// ```text
+/// == Devices ==
+/// My Mac (A1B2C3D4-...)
+/// Nigel's iPhone (17.2) (00008110-001A2B3C4D5E801E)
+///
+/// == Devices Offline ==
+/// Old iPhone (16.4) (00008030-000A1B2C3D4E5F6A)
+///
+/// == Simulators ==
+/// iPhone 15 (17.2) (5A1B2C3D-...)
+/// ```
+///
+/// The Mac itself is the device without an OS version and is skipped.
+pub fn parse_xctrace_devices(output: &str) -> Vec<IosDevice> {
+    let mut devices = Vec::new();
+    let mut state = None;
+
+    for line in output.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix("== ").and_then(|line| line.strip_suffix(" ==")) {
+            state = match section {
+                "Devices" => Some("connected"),
+                "Devices Offline" => Some("offline"),
+                _ => None,
+            };
+            continue;
+        }
+        let state = match state {
+            Some(state) if !line.is_empty() => state,
+            _ => continue,
+        };
+
+        // "<name> (<version>) (<udid>)"; names may contain parentheses themselves
+        let (rest, udid) = match line.strip_suffix(')').and_then(|line| line.rsplit_once(" (")) {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let (name, version) = match rest.strip_suffix(')').and_then(|rest| rest.rsplit_once(" (")) {
+            Some((name, version)) if version.chars().next().is_some_and(|c| c.is_ascii_digit()) => (name, version),
+            _ => continue,
+        };
+
+        devices.push(IosDevice {
+            name: name.to_string(),
+            udid: udid.to_string(),
+            model: None,
+            os_version: Some(version.to_string()),
+            transport: None,
+            state: state.to_string(),
+        });
+    }
+    devices
+}
+
 #[derive(Deserialize)]
 struct SimctlOutput {
     devices: std::collections::HashMap<String, Vec<SimctlDevice>>,
@@ -910,7 +1053,7 @@ struct SimctlDevice {
     is_available: Option<bool>,
 }
 
-fn parse_simulators(json_str: &str) -> SimulatorListResult {
+pub fn parse_simulators(json_str: &str) -> SimulatorListResult {
     let parsed: Result<SimctlOutput, _> = serde_json::from_str(json_str);
 
     match parsed {
@@ -937,15 +1080,156 @@ fn parse_simulators(json_str: &str) -> SimulatorListResult {
     }
 }
 
-/// List available Android emulators using emulator command.
-pub fn list_emulators() -> EmulatorListResult {
-    let output = Command::new("emulator")
-        .arg("-list-avds")
-        .output();
+/// List Android Virtual Devices with their API level and ABI, together with
+/// every device connected to adb (physical or emulator) in any state.
+pub fn list_emulators(runner: &dyn CommandRunner) -> EmulatorListResult {
+    let emulators = list_avd_names(runner);
+
+    let mut devices = list_android_devices(runner);
+    for device in devices.iter_mut().filter(|device| device.is_emulator && device.state == "device") {
+        device.avd = avd_name(runner, &device.serial);
+    }
+
+    let avd_dir = avd_dir();
+    let avds = emulators
+        .iter()
+        .map(|name| {
+            let ini = fs::read_to_string(avd_dir.join(format!("{}.ini", name))).unwrap_or_default();
+            // The .ini points at the AVD's directory, which is usually <name>.avd next to it
+            let path = parse_ini(&ini)
+                .get("path")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| avd_dir.join(format!("{}.avd", name)));
+            let config = fs::read_to_string(path.join("config.ini")).unwrap_or_default();
+
+            let mut avd = parse_avd(name, &ini, &config);
+            avd.serial = devices
+                .iter()
+                .find(|device| device.avd.as_deref() == Some(name.as_str()))
+                .map(|device| device.serial.clone());
+            avd
+        })
+        .collect();
+
+    EmulatorListResult { emulators, avds, devices }
+}
+
+/// Devices connected to adb, from `adb devices -l`.
+pub fn list_android_devices(runner: &dyn CommandRunner) -> Vec<AndroidDevice> {
+    match runner.output("adb", &["devices", "-l"]) {
+        Ok(output) if output.success => parse_adb_devices(&output.stdout_str()),
+        _ => Vec::new(),
+    }
+}
+
+/// `$ANDROID_AVD_HOME`, defaulting to `~/.android/avd`.
+pub fn avd_dir() -> PathBuf {
+    match std::env::var("ANDROID_AVD_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".android").join("avd"),
+    }
+}
+
+/// `key=value` lines of an AVD `.ini` or `config.ini` file.
+fn parse_ini(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Details of the AVD `name` from `<name>.ini` and its `config.ini`:
+///
+/// ```text
+/// target=android-34
+/// ---
+/// avd.ini.displayname=Pixel 7 API 34
+/// abi.type=arm64-v8a
+/// hw.device.name=pixel_7
+/// tag.id=google_apis
+/// image.sysdir.1=system-images/android-34/google_apis/arm64-v8a/
+/// ```
+pub fn parse_avd(name: &str, ini: &str, config: &str) -> Avd {
+    let ini = parse_ini(ini);
+    let config = parse_ini(config);
+
+    // "android-34", either as the target or inside the system image path
+    let api_level = ini
+        .get("target")
+        .into_iter()
+        .chain(config.get("image.sysdir.1"))
+        .flat_map(|value| value.split('/'))
+        .find_map(|part| part.strip_prefix("android-").and_then(|level| level.parse::<u32>().ok()));
+
+    Avd {
+        name: name.to_string(),
+        display_name: config.get("avd.ini.displayname").cloned(),
+        api_level,
+        abi: config.get("abi.type").cloned(),
+        device: config.get("hw.device.name").cloned(),
+        image: config.get("tag.id").cloned(),
+        serial: None,
+    }
+}
+
+/// Devices in `adb devices -l` output, e.g.
+/// `R58M12345  device usb:1-1 product:beyond1 model:SM_G973F device:beyond1 transport_id:2`.
+/// Devices that can't be used yet (`unauthorized`, `offline`, `no permissions`) are kept
+/// with their state so callers can explain why.
+pub fn parse_adb_devices(output: &str) -> Vec<AndroidDevice> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let serial = fields.next()?.to_string();
+            let rest: Vec<&str> = fields.collect();
+            let state = match rest.first()? {
+                // "no permissions (user in plugdev group; ...); see [http://...]"
+                &"no" => "no permissions".to_string(),
+                state => state.to_string(),
+            };
+
+            let property = |key: &str| {
+                rest.iter()
+                    .find_map(|field| field.strip_prefix(key)?.strip_prefix(':'))
+                    .map(|value| value.to_string())
+            };
+            let is_emulator = serial.starts_with("emulator-");
+            let transport = if is_emulator {
+                "emulator"
+            } else if property("usb").is_some() {
+                "usb"
+            } else if serial.contains(':') || serial.contains("._adb-tls-connect.") {
+                "wifi"
+            } else {
+                "unknown"
+            };
+
+            Some(AndroidDevice {
+                model: property("model").map(|model| model.replace('_', " ")),
+                product: property("product"),
+                device: property("device"),
+                transport: transport.to_string(),
+                transport_id: property("transport_id"),
+                is_emulator,
+                avd: None,
+                state,
+                serial,
+            })
+        })
+        .collect()
+}
+
+/// Names of the Android Virtual Devices, from `emulator -list-avds`.
+pub fn list_avd_names(runner: &dyn CommandRunner) -> Vec<String> {
+    let output = runner.output("emulator", &["-list-avds"]);
 
     match output {
-        Ok(out) if out.status.success() => {
-            let emulators: Vec<String> = String::from_utf8_lossy(&out.stdout)
+        Ok(out) if out.success => {
+            out.stdout_str()
                 .lines()
                 .filter(|line| {
                     let trimmed = line.trim();
@@ -956,9 +1240,121 @@ pub fn list_emulators() -> EmulatorListResult {
                     !trimmed.contains('|')
                 })
                 .map(|s| s.to_string())
-                .collect();
-            EmulatorListResult { emulators }
+                .collect()
         },
-        _ => EmulatorListResult { emulators: vec![] },
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    #[test]
+    fn test_check_environment_reports_missing_tools() {
+        let runner = FakeRunner::new()
+            .on("node --version", "v20.11.0\n")
+            .on("npm --version", "10.2.4\n")
+            .on("xcodebuild -version", "Xcode 15.2\n")
+            .on_fail("xcodebuild -checkFirstLaunchStatus", "");
+
+        let result = check_environment(&runner);
+        let node = result.checks.iter().find(|c| c.name == "node").unwrap();
+        let xcode = result.checks.iter().find(|c| c.name == "xcode").unwrap();
+
+        assert!(node.ok);
+        assert_eq!(node.version.as_deref(), Some("v20.11.0"));
+        assert!(!xcode.ok);
+        assert_eq!(xcode.version.as_deref(), Some("Xcode 15.2"));
+        assert_eq!(result.overall_status, "errors");
+    }
+
+    #[test]
+    fn test_parse_simulators_keeps_available_ios_devices() {
+        let json = r#"{"devices": {
+            "com.apple.CoreSimulator.SimRuntime.iOS-17-2": [
+                {"name": "iPhone 15", "udid": "A1", "state": "Booted", "isAvailable": true},
+                {"name": "iPhone 14", "udid": "A2", "state": "Shutdown", "isAvailable": false}
+            ],
+            "com.apple.CoreSimulator.SimRuntime.watchOS-10-2": [
+                {"name": "Apple Watch", "udid": "W1", "state": "Shutdown", "isAvailable": true}
+            ]
+        }}"#;
+
+        let result = parse_simulators(json);
+        assert_eq!(result.simulators.len(), 1);
+        assert_eq!(result.simulators[0].name, "iPhone 15");
+        assert_eq!(result.simulators[0].state, "Booted");
+    }
+
+    #[test]
+    fn test_parse_devicectl_devices() {
+        let devicectl = r#"{"info": {"outcome": "success"}, "result": {"devices": [
+            {"identifier": "1", "deviceProperties": {"name": "QA iPhone", "osVersionNumber": "17.2"},
+             "hardwareProperties": {"udid": "00008110-000A", "marketingName": "iPhone 14 Pro", "platform": "iOS"},
+             "connectionProperties": {"transportType": "localNetwork", "tunnelState": "disconnected"}},
+            {"identifier": "2", "deviceProperties": {"name": "Dev iPhone", "osVersionNumber": "17.4"},
+             "hardwareProperties": {"udid": "00008120-000B", "marketingName": "iPhone 15", "platform": "iOS"},
+             "connectionProperties": {"transportType": "wired", "tunnelState": "connected"}},
+            {"identifier": "3", "deviceProperties": {"name": "Drawer iPad"},
+             "hardwareProperties": {"udid": "00008030-000C", "platform": "iOS"},
+             "connectionProperties": {"tunnelState": "unavailable"}},
+            {"identifier": "4", "deviceProperties": {"name": "Watch"},
+             "hardwareProperties": {"udid": "00008301-000D", "platform": "watchOS"},
+             "connectionProperties": {"transportType": "wired", "tunnelState": "connected"}}
+        ]}}"#;
+        let devices = parse_devicectl_devices(devicectl).unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].transport.as_deref(), Some("wifi"));
+        assert_eq!(devices[1].model.as_deref(), Some("iPhone 15"));
+        assert_eq!(devices[2].state, "offline");
+    }
+
+    #[test]
+    fn test_parse_xctrace_devices_skips_simulators() {
+        let xctrace = "== Devices ==\nStudio Mac (A1B2C3D4-0000)\nNigel's iPhone (2) (17.2) (00008110-000A)\n\n\
+            == Devices Offline ==\nOld iPhone (16.4) (00008030-000C)\n\n== Simulators ==\niPhone 15 (17.2) (5A1B2C3D-0000)\n";
+        let devices = parse_xctrace_devices(xctrace);
+        assert_eq!(devices.len(), 2);
+        assert_eq!((devices[0].name.as_str(), devices[0].os_version.as_deref()), ("Nigel's iPhone (2)", Some("17.2")));
+        assert_eq!(devices[1].state, "offline");
+    }
+
+    #[test]
+    fn test_parse_adb_devices() {
+        let devices = parse_adb_devices("* daemon started successfully\nList of devices attached\n\
+            R58M12345              device usb:1-1 product:beyond1 model:SM_G973F device:beyond1 transport_id:2\n\
+            emulator-5554          device product:sdk_gphone64_arm64 model:sdk_gphone64_arm64 device:emu64a transport_id:1\n\
+            0A1B2C                 unauthorized usb:1-2 transport_id:3\n\
+            192.168.1.20:5555      offline transport_id:4\n\
+            9B8C7D                 no permissions (user in plugdev group; are your udev rules wrong?); see [http://developer.android.com/tools/device.html] usb:1-3\n\n");
+
+        assert_eq!(devices.len(), 5);
+        assert_eq!(devices[0].model.as_deref(), Some("SM G973F"));
+        assert_eq!(devices[0].transport, "usb");
+        assert!(devices[1].is_emulator);
+        assert_eq!(devices[1].transport, "emulator");
+        assert_eq!(devices[2].state, "unauthorized");
+        assert_eq!(devices[2].model, None);
+        assert_eq!((devices[3].state.as_str(), devices[3].transport.as_str()), ("offline", "wifi"));
+        assert_eq!(devices[4].state, "no permissions");
+    }
+
+    #[test]
+    fn test_parse_avd() {
+        let avd = parse_avd(
+            "Pixel_7_API_34",
+            "avd.ini.encoding=UTF-8\npath=/home/me/.android/avd/Pixel_7_API_34.avd\ntarget=android-34\n",
+            "avd.ini.displayname=Pixel 7 API 34\nabi.type=arm64-v8a\nhw.device.name=pixel_7\ntag.id=google_apis\n",
+        );
+        assert_eq!(avd.api_level, Some(34));
+        assert_eq!(avd.abi.as_deref(), Some("arm64-v8a"));
+        assert_eq!(avd.device.as_deref(), Some("pixel_7"));
+
+        // Older AVDs only name the API level in the system image path
+        let avd = parse_avd("Old", "", "image.sysdir.1=system-images/android-29/default/x86/\n");
+        assert_eq!(avd.api_level, Some(29));
+        assert_eq!(avd.display_name, None);
     }
 }
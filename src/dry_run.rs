@@ -0,0 +1,277 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::error::Result;
+use crate::output::{DryRunResult, PlannedDeletion, PlannedKill};
+use crate::process::{Listener, ProcessInfo, Signal};
+use crate::runner::{CommandOutput, CommandRunner, StreamSink};
+use crate::utils::{format_bytes, path_size};
+
+/// Runner for `--dry-run`. Read-only queries (lsof, adb devices, simctl list, ...)
+/// still run so the command sees the real state of the machine; every other
+/// command is recorded instead of executed and reported as successful.
+/// Path removals are recorded with their current size, and signals with the
+/// command line of the process they would stop.
+pub struct DryRunRunner<'a> {
+    inner: &'a dyn CommandRunner,
+    commands: RefCell<Vec<String>>,
+    deletions: RefCell<Vec<PlannedDeletion>>,
+    kills: RefCell<Vec<PlannedKill>>,
+}
+
+impl<'a> DryRunRunner<'a> {
+    pub fn new(inner: &'a dyn CommandRunner) -> Self {
+        DryRunRunner {
+            inner,
+            commands: RefCell::new(Vec::new()),
+            deletions: RefCell::new(Vec::new()),
+            kills: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Everything recorded so far, for the command described by `command`.
+    pub fn report(&self, command: &str) -> DryRunResult {
+        let deletions = self.deletions.borrow().clone();
+        let processes = self.kills.borrow().clone();
+        let commands = self.commands.borrow().clone();
+        let total_bytes = deletions.iter().map(|deletion| deletion.bytes).sum();
+
+        let message = format!(
+            "Would delete {} path(s) ({}), kill {} process(es) and run {} command(s). Nothing was changed.",
+            deletions.len(),
+            format_bytes(total_bytes),
+            processes.len(),
+            commands.len()
+        );
+
+        DryRunResult {
+            command: command.to_string(),
+            deletions,
+            total_bytes,
+            processes,
+            commands,
+            message,
+        }
+    }
+
+    fn record(&self, program: &str, args: &[&str]) {
+        let command_line = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.commands.borrow_mut().push(command_line);
+
+        if let ("adb", ["-s", serial, "emu", "kill"]) = (program, args) {
+            self.kills.borrow_mut().push(PlannedKill {
+                pid: None,
+                process: format!("emulator {}", serial),
+            });
+        }
+    }
+
+    fn is_killed(&self, pid: u32) -> bool {
+        self.kills.borrow().iter().any(|kill| kill.pid == Some(pid))
+    }
+}
+
+/// Commands that only inspect state and are safe to run during a dry run:
+/// process and device listings, version probes (`node --version`, `java -version`)
+/// and read-only git commands such as `git -C . rev-parse HEAD`.
+fn is_query(program: &str, args: &[&str]) -> bool {
+    if let [flag] = args {
+        if matches!(*flag, "--version" | "-version" | "-v" | "-productVersion") {
+            return true;
+        }
+    }
+
+    match program {
+        "lsof" | "ps" | "sw_vers" => true,
+        "git" => {
+            // Skip `-C <dir>`, which only says where to look
+            let args = match args {
+                ["-C", _, rest @ ..] => rest,
+                _ => args,
+            };
+            matches!(args.first(), Some(&("rev-parse" | "status" | "log" | "describe")))
+        }
+        "adb" => matches!(
+            args,
+            ["devices", ..] | ["-s", _, "shell", "getprop", ..] | ["shell", "getprop", ..] | ["-s", _, "emu", "avd", "name"]
+                | ["-s", _, "reverse", "--list"]
+        ),
+        "emulator" => args == ["-list-avds"],
+        "xcrun" => matches!(args, ["simctl", "list", ..] | ["devicectl", "list", ..] | ["xctrace", "list", ..]),
+        _ => false,
+    }
+}
+
+impl CommandRunner for DryRunRunner<'_> {
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        if is_query(program, args) {
+            return self.inner.output(program, args);
+        }
+        self.record(program, args);
+        Ok(CommandOutput { success: true, ..Default::default() })
+    }
+
+    fn status(&self, program: &str, args: &[&str]) -> io::Result<bool> {
+        if is_query(program, args) {
+            return self.inner.status(program, args);
+        }
+        self.record(program, args);
+        Ok(true)
+    }
+
+    fn spawn(&self, program: &str, args: &[&str], _cwd: Option<&str>) -> io::Result<()> {
+        self.record(program, args);
+        Ok(())
+    }
+
+    fn stream(&self, program: &str, args: &[&str], _cwd: Option<&str>, _tee: &StreamSink<'_>) -> io::Result<Option<i32>> {
+        self.record(program, args);
+        Ok(Some(0))
+    }
+
+    fn remove_path(&self, path: &Path) -> Result<Option<u64>> {
+        // Removing a missing path is a no-op, so there is nothing to report
+        if fs::symlink_metadata(path).is_err() {
+            return Ok(None);
+        }
+
+        let bytes = path_size(path);
+        self.deletions.borrow_mut().push(PlannedDeletion {
+            path: path.to_string_lossy().to_string(),
+            bytes,
+        });
+        Ok(Some(bytes))
+    }
+
+    fn tcp_listeners(&self) -> io::Result<Vec<Listener>> {
+        self.inner.tcp_listeners()
+    }
+
+    fn process_info(&self, pid: u32) -> Option<ProcessInfo> {
+        // A process we would have signalled counts as gone, so nothing waits on it
+        if self.is_killed(pid) {
+            return None;
+        }
+        self.inner.process_info(pid)
+    }
+
+    fn send_signal(&self, pid: u32, _signal: Signal) -> io::Result<()> {
+        if !self.is_killed(pid) {
+            let process = self.inner.process_info(pid)
+                .map(|info| info.command)
+                .unwrap_or_else(|| "unknown".to_string());
+            self.kills.borrow_mut().push(PlannedKill { pid: Some(pid), process });
+        }
+        Ok(())
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::android::run_android;
+    use crate::args::BuildOptions;
+    use crate::config::Config;
+    use crate::package_manager::PackageManager;
+    use crate::runner::FakeRunner;
+    use crate::test_support::{EnvGuard, TempDir};
+    use crate::terminal::shell_quote;
+    use crate::utils::{kill_process_logged, launch_packager, LogWriter};
+    use crate::version::Version;
+
+    fn metro_on_8081() -> FakeRunner {
+        FakeRunner::new()
+            .on("listeners", "8081 4242\n")
+            .on("process 4242", "node node_modules/.bin/react-native start\n")
+    }
+
+    #[test]
+    fn test_kills_are_recorded_not_sent() {
+        let inner = metro_on_8081();
+        let dry_runner = DryRunRunner::new(&inner);
+        kill_process_logged(&dry_runner, 8081, Some(&LogWriter::discard())).unwrap();
+
+        // Queries reach the real runner, signals never do
+        assert!(inner.calls().iter().all(|call| call == "listeners" || call == "process 4242"));
+        let report = dry_runner.report("kill metro");
+        assert_eq!(report.processes[0].pid, Some(4242));
+        assert_eq!(report.processes[0].process, "node node_modules/.bin/react-native start");
+        assert!(report.commands.is_empty());
+    }
+
+    #[test]
+    fn test_removals_are_recorded_with_their_size() {
+        let dir = TempDir::new("dry-run");
+        dir.write("Pods/lib.a", vec![0u8; 1500]);
+        let inner = FakeRunner::new();
+        let dry_runner = DryRunRunner::new(&inner);
+
+        assert_eq!(dry_runner.remove_path(&dir.join("Pods")).unwrap(), Some(1500));
+        assert_eq!(dry_runner.remove_path(&dir.join("missing")).unwrap(), None);
+        assert!(dir.join("Pods/lib.a").exists());
+        assert!(inner.calls().is_empty());
+
+        let report = dry_runner.report("clean pods");
+        assert_eq!(report.deletions[0].bytes, 1500);
+        assert_eq!(report.total_bytes, 1500);
+    }
+
+    #[test]
+    fn test_version_and_git_probes_still_run() {
+        assert!(is_query("git", &["-C", ".", "rev-parse", "HEAD"]));
+        assert!(is_query("node", &["--version"]));
+        assert!(is_query("java", &["-version"]));
+        assert!(!is_query("git", &["-C", ".", "clean", "-fdx"]));
+        assert!(!is_query("npm", &["install"]));
+    }
+
+    #[test]
+    fn test_run_android_records_only_the_changes() {
+        let home = TempDir::new("dry-run-home");
+        let mut env = EnvGuard::home(home.path());
+        env.set("RN_RUN_TERMINAL", "tmux");
+        let cwd = std::env::current_dir().unwrap().to_string_lossy().to_string();
+
+        let inner = FakeRunner::new()
+            .on("listeners", "")
+            .on("adb devices", "List of devices attached\nR58M12345\tdevice\n")
+            .on("adb devices -l", "List of devices attached\nR58M12345 device usb:1-1 model:SM_G973F\n")
+            .on("adb -s R58M12345 reverse --list", "");
+        let dry_runner = DryRunRunner::new(&inner);
+        let version = Version::parse("0.74.3").unwrap();
+        run_android(&dry_runner, &Config::default(), &BuildOptions::default(), true, ".", &version, PackageManager::Npm).unwrap();
+
+        // Device listings ran for real; the build itself is recorded as the terminal launch
+        let commands = dry_runner.report("run android").commands;
+        assert_eq!(commands[..3], [
+            format!("watchman watch-del {}", cwd),
+            format!("watchman watch-project {}", cwd),
+            "adb -s R58M12345 reverse tcp:8081 tcp:8081".to_string(),
+        ]);
+        assert_eq!(commands.len(), 4, "{:?}", commands);
+        assert!(commands[3].starts_with(&format!("sh -c cd {}; ", shell_quote(&cwd))), "{}", commands[3]);
+        assert!(commands[3].contains("npx react-native run-android --active-arch-only --deviceId=R58M12345"));
+        assert!(inner.calls().iter().all(|call| !call.starts_with("watchman") && !call.contains("reverse tcp:")));
+    }
+
+    #[test]
+    fn test_terminal_launch_is_recorded_without_detecting_a_terminal() {
+        let mut env = EnvGuard::lock();
+        env.set("RN_RUN_TERMINAL", "none");
+        let inner = FakeRunner::new();
+        let dry_runner = DryRunRunner::new(&inner);
+        launch_packager(&dry_runner, PackageManager::Yarn, Some(8082)).unwrap();
+
+        let commands = dry_runner.report("run ios").commands;
+        assert_eq!(commands.len(), 1);
+        assert!(commands[0].starts_with("sh -c cd ") && commands[0].ends_with("; yarn start --port 8082"), "{}", commands[0]);
+    }
+}
@@ -0,0 +1,242 @@
+use std::time::{Duration, Instant};
+use crate::args::AndroidDeviceCommand;
+use crate::config::Config;
+use crate::diagnostics::list_avd_names;
+use crate::error::{AppError, Result};
+use crate::android::reverse_devices;
+use crate::output::{EmulatorActionResult, ReverseResult};
+use crate::runner::CommandRunner;
+
+/// How long a cold-booting emulator gets to report `sys.boot_completed`.
+pub const BOOT_TIMEOUT: Duration = Duration::from_secs(180);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Console port of the first emulator; each one takes an even port above it.
+const FIRST_CONSOLE_PORT: u16 = 5554;
+
+/// A running emulator: its adb serial and the AVD it was started from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningEmulator {
+    pub serial: String,
+    pub avd: Option<String>,
+}
+
+/// Result of a `devices android` command.
+pub enum AndroidDeviceOutcome {
+    Emulator(EmulatorActionResult),
+    Reverse(ReverseResult),
+}
+
+/// Run a `devices android` command. The AVD defaults to `avd` under `[android]`.
+pub fn execute(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    command: &AndroidDeviceCommand,
+) -> Result<AndroidDeviceOutcome> {
+    let configured = || {
+        config.android.avd.clone().ok_or_else(|| {
+            AppError::Config("no AVD given; pass one or set avd under [android]".to_string())
+        })
+    };
+
+    match command {
+        AndroidDeviceCommand::Start { avd, headless } => {
+            let avd = avd.clone().map_or_else(configured, Ok)?;
+            start(runner, &avd, *headless, BOOT_TIMEOUT).map(AndroidDeviceOutcome::Emulator)
+        }
+        AndroidDeviceCommand::Stop { emulator } => {
+            let emulator = emulator.clone().map_or_else(configured, Ok)?;
+            stop(runner, &emulator).map(AndroidDeviceOutcome::Emulator)
+        }
+        AndroidDeviceCommand::Reverse { device } => {
+            reverse_devices(runner, config.metro_port(), device.as_deref()).map(AndroidDeviceOutcome::Reverse)
+        }
+    }
+}
+
+/// Emulators known to adb, booted or still booting.
+pub fn running_emulators(runner: &dyn CommandRunner) -> Vec<RunningEmulator> {
+    let output = match runner.output("adb", &["devices"]) {
+        Ok(output) if output.success => output.stdout_str(),
+        _ => return Vec::new(),
+    };
+
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|serial| serial.starts_with("emulator-"))
+        .map(|serial| RunningEmulator {
+            serial: serial.to_string(),
+            avd: avd_name(runner, serial),
+        })
+        .collect()
+}
+
+/// AVD name of a running emulator, from its console (`adb emu avd name`).
+pub fn avd_name(runner: &dyn CommandRunner, serial: &str) -> Option<String> {
+    let output = runner.output("adb", &["-s", serial, "emu", "avd", "name"]).ok()?;
+    // The console answers with the name followed by "OK"
+    output
+        .stdout_str()
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && *line != "OK")
+        .map(str::to_string)
+}
+
+/// Start `avd` and wait until Android has finished booting. Headless emulators
+/// run without a window, audio or boot animation (for CI). An AVD that is
+/// already running is reused.
+pub fn start(runner: &dyn CommandRunner, avd: &str, headless: bool, timeout: Duration) -> Result<EmulatorActionResult> {
+    if !list_avd_names(runner).iter().any(|name| name == avd) {
+        return Err(AppError::AvdNotFound(avd.to_string()));
+    }
+
+    let running = running_emulators(runner);
+    if let Some(emulator) = running.iter().find(|emulator| emulator.avd.as_deref() == Some(avd)) {
+        let waited_ms = wait_for_boot(runner, avd, &emulator.serial, timeout)?;
+        return Ok(EmulatorActionResult {
+            action: "start".to_string(),
+            avd: avd.to_string(),
+            serial: emulator.serial.clone(),
+            changed: false,
+            waited_ms: Some(waited_ms),
+            message: format!("{} is already running as {}", avd, emulator.serial),
+        });
+    }
+
+    // Choose the console port so the serial is known before adb sees the device
+    let port = free_console_port(&running);
+    let port_arg = port.to_string();
+    let mut args = vec!["-avd", avd, "-port", &port_arg];
+    if headless {
+        args.extend(["-no-window", "-no-audio", "-no-boot-anim"]);
+    }
+    runner
+        .spawn("emulator", &args, None)
+        .map_err(|e| AppError::CommandFailed(format!("emulator -avd {}: {}", avd, e)))?;
+
+    let serial = format!("emulator-{}", port);
+    let waited_ms = wait_for_boot(runner, avd, &serial, timeout)?;
+    Ok(EmulatorActionResult {
+        action: "start".to_string(),
+        avd: avd.to_string(),
+        message: format!("Started {} as {} (booted in {:.1}s)", avd, serial, waited_ms as f64 / 1000.0),
+        serial,
+        changed: true,
+        waited_ms: Some(waited_ms),
+    })
+}
+
+/// Shut down a running emulator, given its AVD name or adb serial.
+pub fn stop(runner: &dyn CommandRunner, avd_or_serial: &str) -> Result<EmulatorActionResult> {
+    let emulator = running_emulators(runner)
+        .into_iter()
+        .find(|emulator| emulator.serial == avd_or_serial || emulator.avd.as_deref() == Some(avd_or_serial))
+        .ok_or_else(|| AppError::EmulatorNotRunning(avd_or_serial.to_string()))?;
+
+    let output = runner
+        .output("adb", &["-s", &emulator.serial, "emu", "kill"])
+        .map_err(|_| AppError::CommandFailed(format!("adb -s {} emu kill", emulator.serial)))?;
+    if !output.success {
+        return Err(AppError::CommandFailed(format!("adb -s {} emu kill: {}", emulator.serial, output.stderr_str().trim())));
+    }
+
+    let avd = emulator.avd.unwrap_or_else(|| emulator.serial.clone());
+    Ok(EmulatorActionResult {
+        action: "stop".to_string(),
+        message: format!("Stopped {} ({})", avd, emulator.serial),
+        avd,
+        serial: emulator.serial,
+        changed: true,
+        waited_ms: None,
+    })
+}
+
+/// Lowest even console port not taken by a running emulator.
+fn free_console_port(running: &[RunningEmulator]) -> u16 {
+    (FIRST_CONSOLE_PORT..)
+        .step_by(2)
+        .find(|port| !running.iter().any(|emulator| emulator.serial == format!("emulator-{}", port)))
+        .unwrap_or(FIRST_CONSOLE_PORT)
+}
+
+/// Poll `sys.boot_completed` on `serial` until it reads 1. Returns the time waited.
+fn wait_for_boot(runner: &dyn CommandRunner, avd: &str, serial: &str, timeout: Duration) -> Result<u64> {
+    // Nothing was started during a dry run, so there is nothing to wait for
+    if runner.is_dry_run() {
+        return Ok(0);
+    }
+
+    let started = Instant::now();
+    loop {
+        // Fails while the device is still offline, which just means "not yet"
+        let booted = runner
+            .output("adb", &["-s", serial, "shell", "getprop", "sys.boot_completed"])
+            .map(|output| output.success && output.stdout_str().trim() == "1")
+            .unwrap_or(false);
+        if booted {
+            return Ok(started.elapsed().as_millis() as u64);
+        }
+
+        let remaining = timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Err(AppError::EmulatorBootTimeout(avd.to_string(), timeout));
+        }
+        std::thread::sleep(POLL_INTERVAL.min(remaining));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    fn two_avds_one_running() -> FakeRunner {
+        FakeRunner::new()
+            .on("emulator -list-avds", "Pixel_7\nPixel_Tablet\n")
+            .on("adb devices", "List of devices attached\nemulator-5554\tdevice\n")
+            .on("adb -s emulator-5554 emu avd name", "Pixel_7\r\nOK\r\n")
+            .on("adb -s emulator-5554 shell getprop sys.boot_completed", "1\n")
+            .on("emulator -avd Pixel_Tablet -port 5556 -no-window -no-audio -no-boot-anim", "")
+            .on("adb -s emulator-5556 shell getprop sys.boot_completed", "1\n")
+            .on("adb -s emulator-5554 emu kill", "")
+    }
+
+    #[test]
+    fn test_start_uses_next_free_console_port() {
+        // A new emulator gets the next free console port, so its serial is known up front
+        let started = start(&two_avds_one_running(), "Pixel_Tablet", true, Duration::from_secs(1)).unwrap();
+        assert_eq!(started.serial, "emulator-5556");
+        assert!(started.changed);
+    }
+
+    #[test]
+    fn test_start_reuses_running_emulator() {
+        let runner = two_avds_one_running();
+        let reused = start(&runner, "Pixel_7", false, Duration::from_secs(1)).unwrap();
+        assert_eq!(reused.serial, "emulator-5554");
+        assert!(!reused.changed);
+        assert!(matches!(start(&runner, "Nexus_5", false, Duration::ZERO), Err(AppError::AvdNotFound(_))));
+    }
+
+    #[test]
+    fn test_stop_by_avd_name() {
+        let runner = two_avds_one_running();
+        assert_eq!(stop(&runner, "Pixel_7").unwrap().serial, "emulator-5554");
+        assert!(matches!(stop(&runner, "Pixel_Tablet"), Err(AppError::EmulatorNotRunning(_))));
+    }
+
+    #[test]
+    fn test_start_times_out_without_boot_completed() {
+        let runner = FakeRunner::new()
+            .allow_unscripted()
+            .on("emulator -list-avds", "Pixel_7\n")
+            .on("adb devices", "List of devices attached\n");
+        assert!(matches!(
+            start(&runner, "Pixel_7", false, Duration::ZERO),
+            Err(AppError::EmulatorBootTimeout(_, _))
+        ));
+        assert!(runner.calls().contains(&"emulator -avd Pixel_7 -port 5554".to_string()));
+    }
+}
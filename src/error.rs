@@ -1,4 +1,6 @@
 use std::fmt;
+use crate::known_errors::KnownErrors;
+use crate::package_manager::{current_package_manager, PackageManager};
 
 #[derive(Debug)]
 pub enum AppError {
@@ -9,6 +11,28 @@ pub enum AppError {
     CommandFailed(String),
     InvalidVersion(String),
     ProcessError(String),
+    TerminalNotFound,
+    UnsupportedPlatform(String),
+    Config(String),
+    DryRunUnsupported(String),
+    PermissionDenied(String),
+    RemoveFailed(String),
+    MetroPortInUse(u16, String),
+    MetroNotReady(u16, std::time::Duration),
+    MetroNotRunning(u16),
+    MetroRequestFailed(String),
+    PortInUse(u16, String),
+    SimulatorNotFound(String),
+    AmbiguousDevice(String, Vec<String>),
+    SimulatorSetup(String),
+    AvdNotFound(String),
+    AndroidDeviceNotFound(String),
+    AndroidDeviceUnavailable(String, String),
+    ReverseFailed(String, String),
+    EmulatorNotRunning(String),
+    EmulatorBootTimeout(String, std::time::Duration),
+    UnknownErrorId(String),
+    LogNotFound(String),
 }
 
 impl fmt::Display for AppError {
@@ -21,6 +45,137 @@ impl fmt::Display for AppError {
             AppError::CommandFailed(cmd) => write!(f, "Command failed: {}", cmd),
             AppError::InvalidVersion(version) => write!(f, "Invalid version format: {}", version),
             AppError::ProcessError(msg) => write!(f, "Process error: {}", msg),
+            AppError::TerminalNotFound => write!(f, "No supported terminal found (tried tmux, gnome-terminal, konsole, xterm)"),
+            AppError::UnsupportedPlatform(msg) => write!(f, "Unsupported platform: {}", msg),
+            AppError::Config(msg) => write!(f, "Invalid config: {}", msg),
+            AppError::DryRunUnsupported(command) => write!(f, "--dry-run is not supported for {}", command),
+            AppError::PermissionDenied(path) => write!(f, "Permission denied removing {}", path),
+            AppError::RemoveFailed(msg) => write!(f, "Failed to remove {}", msg),
+            AppError::MetroPortInUse(port, project_root) => write!(f, "Port {} is used by Metro for another project ({})", port, project_root),
+            AppError::MetroNotReady(port, timeout) => write!(f, "Metro did not report ready on port {} within {}s", port, timeout.as_secs_f64()),
+            AppError::MetroNotRunning(port) => write!(f, "Metro is not running on port {}", port),
+            AppError::MetroRequestFailed(msg) => write!(f, "Metro request failed: {}", msg),
+            AppError::PortInUse(port, process) => write!(f, "Port {} is in use by {}, which is not Metro", port, process),
+            AppError::SimulatorNotFound(simulator) => write!(f, "No iOS simulator named or with UDID '{}'", simulator),
+            AppError::AmbiguousDevice(name, matches) => write!(f, "'{}' matches {} devices: {}", name, matches.len(), matches.join(", ")),
+            AppError::SimulatorSetup(msg) => write!(f, "Cannot create simulator: {}", msg),
+            AppError::AvdNotFound(avd) => write!(f, "No Android Virtual Device named '{}'", avd),
+            AppError::AndroidDeviceNotFound(device) => write!(f, "No connected Android device matches '{}'", device),
+            AppError::AndroidDeviceUnavailable(serial, state) => write!(f, "Android device {} is {}", serial, state),
+            AppError::ReverseFailed(serial, reason) => write!(f, "adb reverse failed on {}: {}", serial, reason),
+            AppError::EmulatorNotRunning(emulator) => write!(f, "No running emulator matches '{}'", emulator),
+            AppError::EmulatorBootTimeout(avd, timeout) => write!(f, "Emulator {} did not finish booting within {}s", avd, timeout.as_secs()),
+            AppError::UnknownErrorId(id) => write!(f, "No known error with id '{}'", id),
+            AppError::LogNotFound(name) => write!(f, "No build log named '{}'", name),
+        }
+    }
+}
+
+impl AppError {
+    /// Get a suggested fix for common errors (for AI consumption).
+    /// Errors carrying a tool's output are first matched against the known errors catalogue.
+    pub fn fix(&self) -> Option<String> {
+        if let Some(known) = self.tool_output().and_then(|output| KnownErrors::shared().match_text(output)) {
+            return Some(known.suggestion());
+        }
+
+        match self {
+            AppError::ReactNativeNotFound => {
+                Some("Make sure you're in a React Native project directory with package.json containing react-native dependency".to_string())
+            },
+            AppError::CommandFailed(cmd) => {
+                if cmd.contains("pod install") {
+                    Some("Try: rn-run clean pods --pod-install".to_string())
+                } else if cmd.contains("node_modules") {
+                    let package_manager = current_package_manager().unwrap_or(PackageManager::Npm);
+                    Some(format!("Try: rn-run clean modules && {}", package_manager.install()))
+                } else {
+                    None
+                }
+            },
+            AppError::InvalidVersion(_) => {
+                Some("Check the react-native version in package.json, or run your package manager's install so node_modules/react-native is present".to_string())
+            },
+            AppError::Config(_) => {
+                Some("Fix the reported key in .rn-run.toml or ~/.config/rn-run/config.toml. Use 'rn-run --print-config' to check the result".to_string())
+            },
+            AppError::DryRunUnsupported(_) => {
+                Some("--dry-run applies to commands that change something: run, clean, devices ios, devices android, --pod-install, --kill-metro, --quit-simulator, --delete-simulators and --delete-emulators".to_string())
+            },
+            AppError::PermissionDenied(path) => {
+                Some(format!("Check who owns {0}. If it was created with sudo, run: sudo chown -R $USER {0}", path))
+            },
+            AppError::MetroPortInUse(port, _) => {
+                let other_port = match port.checked_add(1) {
+                    Some(other) => format!(", e.g. 'rn-run run ios --port {}'", other),
+                    None => String::new(),
+                };
+                Some(format!("Run this project's Metro on another port{}, or set port under [metro] in .rn-run.toml. 'rn-run metro status' lists running servers", other_port))
+            },
+            AppError::MetroNotReady(port, _) => {
+                let package_manager = current_package_manager().unwrap_or(PackageManager::Npm);
+                let start = package_manager.run_script_with_args("start", &format!("--port {}", port));
+                Some(format!("Check the Metro terminal window for errors, or start Metro yourself with '{}' and run again", start))
+            },
+            AppError::MetroNotRunning(port) => {
+                Some(format!("Start the app with 'rn-run run ios' or 'rn-run run android', or pass --port if Metro runs on another port than {}", port))
+            },
+            AppError::MetroRequestFailed(msg) if msg.contains("no app is connected") => {
+                Some("Open the app on a simulator or device so it connects to Metro, then try again".to_string())
+            },
+            AppError::PortInUse(port, _) => {
+                Some(format!("Stop that process, or run Metro on another port with --port (currently {})", port))
+            },
+            AppError::SimulatorNotFound(_) => {
+                Some("Run 'rn-run devices list ios' to see available simulators, or create one with 'rn-run devices ios create'".to_string())
+            },
+            AppError::AmbiguousDevice(_, _) => {
+                Some("Pass the UDID or serial instead of the name".to_string())
+            },
+            AppError::AndroidDeviceNotFound(_) => {
+                Some("Run 'rn-run devices list android' to see connected devices, and pass a serial or model from it".to_string())
+            },
+            AppError::AndroidDeviceUnavailable(_, state) if state == "unauthorized" => {
+                Some("Unlock the device and accept the 'Allow USB debugging' prompt, then run again".to_string())
+            },
+            AppError::ReverseFailed(serial, _) => {
+                Some(format!("Reconnect the device and run 'rn-run devices android reverse {}'. If it keeps failing, run 'adb kill-server' first", serial))
+            },
+            AppError::AndroidDeviceUnavailable(_, _) => {
+                Some("Reconnect the device, or run 'adb kill-server' and try again".to_string())
+            },
+            AppError::SimulatorSetup(_) => {
+                Some("Run 'xcrun simctl list devicetypes' and 'xcrun simctl list runtimes' to see what is installed. Install more runtimes in Xcode > Settings > Platforms".to_string())
+            },
+            AppError::AvdNotFound(_) => {
+                Some("Run 'rn-run devices list android' to see available AVDs, or create one in Android Studio > Device Manager".to_string())
+            },
+            AppError::EmulatorNotRunning(_) => {
+                Some("Run 'adb devices' to see running emulators; pass an AVD name or a serial such as emulator-5554".to_string())
+            },
+            AppError::EmulatorBootTimeout(avd, _) => {
+                Some(format!("Start it with a window to see what is wrong ('emulator -avd {}'), or cold boot it from Android Studio > Device Manager", avd))
+            },
+            AppError::TerminalNotFound => {
+                Some("Install gnome-terminal, konsole or xterm, or run rn-run inside a tmux session".to_string())
+            },
+            AppError::UnknownErrorId(_) => {
+                Some("Run 'rn-run explain' to list known errors".to_string())
+            },
+            AppError::LogNotFound(_) => {
+                Some("Run 'rn-run logs list' to see available logs".to_string())
+            },
+            _ => None,
+        }
+    }
+
+    /// What an external tool reported: the failed command with its stderr, or the
+    /// OS error. The catalogue describes tool output, so errors worded by rn-run
+    /// itself keep their own fix even when a port or path happens to match a pattern.
+    fn tool_output(&self) -> Option<&str> {
+        match self {
+            AppError::CommandFailed(output) | AppError::ProcessError(output) | AppError::ReverseFailed(_, output) => Some(output),
+            _ => None,
         }
     }
 }
@@ -47,4 +202,39 @@ impl From<serde_json::Error> for AppError {
     }
 }
 
-pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, AppError>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{EnvGuard, TempDir};
+
+    #[test]
+    fn test_metro_not_ready_fix_starts_metro_with_the_package_manager() {
+        // Outside a React Native project the package manager falls back to npm
+        let home = TempDir::new("metro-not-ready-home");
+        let _env = EnvGuard::home(home.path());
+        let fix = AppError::MetroNotReady(8082, std::time::Duration::from_secs(60)).fix().unwrap();
+        assert!(fix.contains("'npm run start -- --port 8082'"), "{}", fix);
+    }
+
+    #[test]
+    fn test_only_tool_output_is_matched_against_the_catalogue() {
+        let boot_failed = KnownErrors::builtin().find("simulator-boot-failed").unwrap().suggestion();
+        let error = AppError::CommandFailed("xcrun simctl boot 1A2B: Unable to boot device in current state: Booted".to_string());
+        assert_eq!(error.fix(), Some(boot_failed));
+
+        // A log name is not tool output, even if it contains a pattern
+        let error = AppError::LogNotFound("EADDRINUSE".to_string());
+        assert_eq!(error.fix().as_deref(), Some("Run 'rn-run logs list' to see available logs"));
+    }
+
+    #[test]
+    fn test_metro_port_fix_has_no_port_past_65535() {
+        // fix() reads the user catalogue under HOME
+        let _env = EnvGuard::lock();
+        let fix = AppError::MetroPortInUse(8081, "/work/other-app".to_string()).fix().unwrap();
+        assert!(fix.contains("--port 8082"));
+        let fix = AppError::MetroPortInUse(u16::MAX, "/work/other-app".to_string()).fix().unwrap();
+        assert!(!fix.contains("--port"));
+    }
+}
@@ -1,42 +1,83 @@
-use crate::args::Args;
-use crate::error::Result;
+use std::path::Path;
+use crate::args::BuildOptions;
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::metro::ensure_port_available;
+use crate::package_manager::PackageManager;
+use crate::runner::CommandRunner;
+use crate::version::Version;
+use crate::terminal::is_macos;
 use crate::utils::{
-    clean_install, close_terminal_windows, deep_clean, is_version_greater_or_equal,
-    kill_process_logged, launch_packager, launch_sim, quit_simulator, watch_directory_logged,
-    LogWriter,
+    clean_install, close_terminal_windows, deep_clean,
+    kill_process_logged, launch_packager, launch_sim, quit_simulator, run_build_foreground,
+    wait_for_packager, watch_directory_logged, LogMetadata, LogWriter, RunOutcome,
 };
 
-pub fn run_ios(args: &Args, _current_dir: &str, react_native_version: &str) -> Result<()> {
-    // Create log writer at the start to capture all output
-    let log = LogWriter::new("ios")?;
+/// Run the iOS app. The outcome holds the build result when running in the foreground,
+/// and Metro's readiness when rn-run started the packager itself.
+pub fn run_ios(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    build: &BuildOptions,
+    json: bool,
+    project_dir: &str,
+    react_native_version: &Version,
+    package_manager: PackageManager,
+) -> Result<RunOutcome> {
+    if !is_macos() {
+        return Err(AppError::UnsupportedPlatform("iOS builds require macOS with Xcode".to_string()));
+    }
+
+    // Create log writer at the start to capture all output.
+    // A dry run only reports what would happen, so it leaves the build logs alone.
+    let log = if runner.is_dry_run() {
+        LogWriter::discard()
+    } else {
+        LogWriter::new(LogMetadata::new(runner, "ios", project_dir, react_native_version), config.max_logs())?
+            .echo_to_stderr(json)
+    };
+
+    // Never kill another project's Metro; restarting our own is fine
+    ensure_port_available(config.metro_port(), Path::new(project_dir))?;
+    kill_process_logged(runner, config.metro_port(), Some(&log))?;
+    quit_simulator(runner)?;
 
-    kill_process_logged(Some(&log))?;
-    quit_simulator()?;
-    close_terminal_windows()?;
+    // In the foreground the build runs in this terminal, so leave its window open
+    if !build.foreground {
+        close_terminal_windows(runner)?;
+    }
 
-    if args.upgrade {
-        deep_clean("ios")?;
+    if build.upgrade {
+        deep_clean(runner, package_manager, "ios", &log)?;
     }
 
-    if args.clean_install || args.upgrade {
-        clean_install(&react_native_version, "ios")?;
+    if build.clean_install || build.upgrade {
+        clean_install(runner, package_manager, "ios", &log)?;
     }
 
     // Get current directory for watchman
     let current_dir = std::env::current_dir()
-        .map_err(|_| crate::error::AppError::CurrentDir)?
+        .map_err(|_| AppError::CurrentDir)?
         .to_string_lossy()
         .to_string();
 
-    watch_directory_logged(&current_dir, Some(&log))?;
+    watch_directory_logged(runner, &current_dir, Some(&log))?;
+
+    if build.foreground {
+        log.log("packager will be launched by the react-native CLI");
+        let build = run_build_foreground(runner, config, package_manager, react_native_version, "ios", &log)?;
+        return Ok(RunOutcome { build: Some(build), metro: None });
+    }
 
-    if is_version_greater_or_equal(react_native_version, "0.74") {
+    let metro = if react_native_version.at_least(0, 74) {
         log.log("packager will be launched via npx");
+        None
     } else {
-        launch_packager()?;
-    }
+        launch_packager(runner, package_manager, config.metro.port)?;
+        wait_for_packager(runner, config, &log)?
+    };
 
-    let _log_path = launch_sim(&react_native_version, args, &log)?;
+    let _log_path = launch_sim(runner, config, package_manager, react_native_version, "ios", &log)?;
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(RunOutcome { build: None, metro })
+}
@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use crate::config::user_config_path;
+use crate::error::{AppError, Result};
+
+/// The catalogue that ships with rn-run.
+const BUILTIN: &str = include_str!("known_errors.toml");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalogue {
+    #[serde(default)]
+    error: Vec<KnownError>,
+}
+
+/// A known build failure and how to fix it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KnownError {
+    pub id: String,
+    pub title: String,
+    pub explanation: String,
+    pub fix: String,
+    /// Text that identifies the failure; any one is enough, case is ignored
+    pub patterns: Vec<String>,
+}
+
+impl KnownError {
+    fn matches(&self, text: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| text.contains(&pattern.to_lowercase()))
+    }
+
+    /// The fix as a one-line suggestion
+    pub fn suggestion(&self) -> String {
+        format!("{}: {} (see 'rn-run explain {}')", self.title, self.fix, self.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KnownErrors {
+    pub errors: Vec<KnownError>,
+}
+
+impl KnownErrors {
+    /// The built-in catalogue only.
+    pub fn builtin() -> Self {
+        KnownErrors {
+            errors: parse(BUILTIN, "built-in known errors").expect("built-in known_errors.toml is valid"),
+        }
+    }
+
+    /// The built-in catalogue extended by the user file, whose entries come
+    /// first and replace built-in entries with the same id.
+    pub fn load() -> Result<Self> {
+        let mut errors = match user_known_errors_path() {
+            Some(path) if path.exists() => {
+                let content = fs::read_to_string(&path)?;
+                parse(&content, &path.to_string_lossy())?
+            }
+            _ => Vec::new(),
+        };
+
+        for builtin in Self::builtin().errors {
+            if !errors.iter().any(|error| error.id == builtin.id) {
+                errors.push(builtin);
+            }
+        }
+        Ok(KnownErrors { errors })
+    }
+
+    /// `load`, falling back to the built-in catalogue when the user file is
+    /// broken. For suggestions on an error path, where a second error would
+    /// hide the first.
+    pub fn load_or_builtin() -> Self {
+        Self::load().unwrap_or_else(|_| Self::builtin())
+    }
+
+    /// `load_or_builtin`, read once per run. Error suggestions come from here,
+    /// so a cleanup with several failing steps reads the user file only once.
+    pub fn shared() -> &'static KnownErrors {
+        static SHARED: OnceLock<KnownErrors> = OnceLock::new();
+        SHARED.get_or_init(Self::load_or_builtin)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&KnownError> {
+        self.errors.iter().find(|error| error.id.eq_ignore_ascii_case(id))
+    }
+
+    /// The first entry whose patterns occur in `text`.
+    pub fn match_text(&self, text: &str) -> Option<&KnownError> {
+        let text = text.to_lowercase();
+        self.errors.iter().find(|error| error.matches(&text))
+    }
+}
+
+/// `known_errors.toml` next to the user config.
+pub fn user_known_errors_path() -> Option<PathBuf> {
+    Some(user_config_path()?.with_file_name("known_errors.toml"))
+}
+
+fn parse(content: &str, source: &str) -> Result<Vec<KnownError>> {
+    let catalogue: Catalogue =
+        toml::from_str(content).map_err(|e| AppError::Config(format!("{}: {}", source, e.message())))?;
+
+    for error in &catalogue.error {
+        if error.patterns.iter().all(|pattern| pattern.trim().is_empty()) {
+            return Err(AppError::Config(format!("{}: '{}' has no patterns", source, error.id)));
+        }
+    }
+    Ok(catalogue.error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{EnvGuard, TempDir};
+
+    #[test]
+    fn test_builtin_catalogue_matches_failures() {
+        let builtin = KnownErrors::builtin();
+        let known = builtin.match_text("error: listen EADDRINUSE: address already in use :::8081").unwrap();
+        assert_eq!(known.id, "metro-port-in-use");
+        assert!(known.suggestion().contains("rn-run explain metro-port-in-use"));
+        assert_eq!(builtin.match_text("FAILURE: sdk location NOT FOUND. Define a valid SDK").unwrap().id, "android-sdk-not-found");
+        assert!(builtin.match_text("BUILD SUCCESSFUL in 12s").is_none());
+        assert!(builtin.find("no-bundle-url").is_some());
+    }
+
+    #[test]
+    fn test_user_entries_come_first_and_replace_builtin() {
+        let config_home = TempDir::new("known-errors");
+        config_home.write("rn-run/known_errors.toml", r#"
+            [[error]]
+            id = "metro-port-in-use"
+            title = "Port taken"
+            explanation = "Our CI agents run Metro on 8081."
+            fix = "rn-run run ios --port 8082"
+            patterns = ["EADDRINUSE"]
+
+            [[error]]
+            id = "signing"
+            title = "Signing failed"
+            explanation = "The provisioning profile expired."
+            fix = "Renew it in the developer portal"
+            patterns = ["requires a provisioning profile"]
+        "#);
+        let mut env = EnvGuard::lock();
+        env.set("XDG_CONFIG_HOME", config_home.path());
+
+        let builtin = KnownErrors::builtin();
+        let loaded = KnownErrors::load().unwrap();
+        assert_eq!(loaded.errors.len(), builtin.errors.len() + 1);
+        assert_eq!(loaded.errors[1].id, "signing");
+        assert_eq!(loaded.find("metro-port-in-use").unwrap().title, "Port taken");
+        assert_eq!(loaded.match_text("Requires a provisioning profile").unwrap().id, "signing");
+    }
+
+    #[test]
+    fn test_broken_user_file_falls_back_to_builtin() {
+        let config_home = TempDir::new("known-errors");
+        config_home.write("rn-run/known_errors.toml", "[[error]]\nid = \"empty\"\n");
+        let mut env = EnvGuard::lock();
+        env.set("XDG_CONFIG_HOME", config_home.path());
+
+        assert!(matches!(KnownErrors::load(), Err(AppError::Config(_))));
+        assert_eq!(KnownErrors::load_or_builtin().errors, KnownErrors::builtin().errors);
+    }
+}
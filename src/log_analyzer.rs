@@ -0,0 +1,354 @@
+use crate::known_errors::KnownErrors;
+use crate::output::{BuildFailure, BuildFailureReport};
+use crate::utils::strip_ansi_codes;
+
+/// Most context lines kept with a failure.
+const MAX_EXCERPT_LINES: usize = 8;
+
+/// Find what made a build fail in a captured build log: compiler diagnostics
+/// from Xcode, Kotlin and javac, Xcode's "The following build commands failed"
+/// list, Gradle's "What went wrong" blocks, CocoaPods `[!]` errors and `npm ERR!`
+/// output. Failures are reported in log order, each once. The log is also
+/// matched against the `known` catalogue.
+pub fn analyze_log(content: &str, known: &KnownErrors) -> BuildFailureReport {
+    let content = strip_ansi_codes(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut failures: Vec<BuildFailure> = Vec::new();
+
+    let mut index = 0;
+    while index < lines.len() {
+        let (found, consumed) = parse_failure(&lines, index);
+        for failure in found {
+            let duplicate = failures.iter().any(|seen| {
+                seen.category == failure.category
+                    && seen.message == failure.message
+                    && seen.file == failure.file
+                    && seen.line == failure.line
+            });
+            if !duplicate {
+                failures.push(failure);
+            }
+        }
+        index += consumed.max(1);
+    }
+
+    let known_error = known.match_text(&content).cloned();
+    let message = match (failures.first(), &known_error) {
+        (None, None) => "No build errors found".to_string(),
+        (None, Some(known_error)) => format!("Known issue: {}", known_error.title),
+        (Some(first), _) => format!("Found {} error(s); first: {}", failures.len(), first.message),
+    };
+    BuildFailureReport {
+        failed: !failures.is_empty(),
+        failures,
+        known_error,
+        message,
+    }
+}
+
+/// Failures starting at `lines[index]` and how many lines they span.
+fn parse_failure(lines: &[&str], index: usize) -> (Vec<BuildFailure>, usize) {
+    let line = lines[index].trim();
+
+    if line == "The following build commands failed:" {
+        return parse_failed_commands(lines, index + 1);
+    }
+    if line == "* What went wrong:" {
+        return parse_gradle_block(lines, index + 1);
+    }
+    if let Some(message) = line.strip_prefix("[!] ") {
+        let excerpt = block(lines, index + 1, |line| !line.trim().is_empty());
+        let consumed = 1 + excerpt.len();
+        return (vec![failure("cocoapods", message, excerpt)], consumed);
+    }
+    if is_npm_error(line) {
+        return parse_npm_block(lines, index);
+    }
+    if let Some(rest) = line.strip_prefix("e: ") {
+        if let Some(failure) = parse_kotlin_diagnostic(rest) {
+            return (vec![failure], 1);
+        }
+    }
+    if let Some(mut failure) = parse_compiler_diagnostic(line) {
+        // The offending source line and the caret under it follow the diagnostic
+        failure.excerpt = block(lines, index + 1, |line| {
+            let line = line.trim();
+            !line.is_empty() && parse_compiler_diagnostic(line).is_none() && !line.starts_with("** ")
+        })
+        .into_iter()
+        .take(2)
+        .collect();
+        return (vec![failure], 1);
+    }
+    if let Some(message) = line.strip_prefix("xcodebuild: error: ").or_else(|| line.strip_prefix("error: ")) {
+        return (vec![failure("xcode", message, Vec::new())], 1);
+    }
+    if line.starts_with("ld: ") || line.starts_with("clang: error: ") {
+        return (vec![failure("xcode", line, Vec::new())], 1);
+    }
+
+    (Vec::new(), 1)
+}
+
+fn failure(category: &str, message: &str, excerpt: Vec<String>) -> BuildFailure {
+    BuildFailure {
+        category: category.to_string(),
+        message: message.trim().to_string(),
+        file: None,
+        line: None,
+        column: None,
+        task: None,
+        excerpt,
+    }
+}
+
+/// Lines from `start` while `keep` holds, up to `MAX_EXCERPT_LINES`.
+fn block(lines: &[&str], start: usize, keep: impl Fn(&str) -> bool) -> Vec<String> {
+    lines
+        .iter()
+        .skip(start)
+        .take_while(|line| keep(line))
+        .take(MAX_EXCERPT_LINES)
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+/// `path:line:col: error: message` from clang/swiftc, or `path:line: error: message` from javac.
+fn parse_compiler_diagnostic(line: &str) -> Option<BuildFailure> {
+    let (location, message) = line
+        .split_once(": fatal error: ")
+        .or_else(|| line.split_once(": error: "))?;
+
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next()?.parse::<u32>().ok()?;
+    let (file, line_number, column) = match (parts.next(), parts.next()) {
+        (Some(line_number), Some(file)) if line_number.parse::<u32>().is_ok() => {
+            (file, line_number.parse::<u32>().ok(), Some(last))
+        }
+        // javac only reports the line
+        (Some(file), Some(rest)) => (&location[..rest.len() + 1 + file.len()], Some(last), None),
+        (Some(file), None) => (file, Some(last), None),
+        _ => return None,
+    };
+    if !file.starts_with('/') && !file.contains('/') {
+        return None;
+    }
+
+    let category = match file.rsplit('.').next() {
+        Some("java") => "java",
+        Some("kt") | Some("kts") => "kotlin",
+        _ => "xcode",
+    };
+    Some(BuildFailure {
+        file: Some(file.to_string()),
+        line: line_number,
+        column,
+        ..failure(category, message, Vec::new())
+    })
+}
+
+/// Kotlin compiler errors, after the `e: ` prefix. Kotlin 1.9+ prints
+/// `file:///path/Main.kt:12:5 message`, older versions `/path/Main.kt: (12, 5): message`.
+fn parse_kotlin_diagnostic(rest: &str) -> Option<BuildFailure> {
+    let rest = rest.strip_prefix("file://").unwrap_or(rest);
+
+    let (file, line, column, message) = if let Some((file, rest)) = rest.split_once(": (") {
+        let (position, message) = rest.split_once("): ")?;
+        let (line, column) = position.split_once(", ")?;
+        (file, line.parse().ok(), column.parse().ok(), message)
+    } else {
+        let (location, message) = rest.split_once(' ')?;
+        let mut parts = location.rsplitn(3, ':');
+        let column = parts.next()?.parse().ok();
+        let line = parts.next()?.parse().ok();
+        (parts.next()?, line, column, message)
+    };
+
+    Some(BuildFailure {
+        file: Some(file.to_string()),
+        line,
+        column,
+        ..failure("kotlin", message, Vec::new())
+    })
+}
+
+/// The commands listed after "The following build commands failed:", e.g.
+/// `CompileSwift normal arm64 /path/App.swift (in target 'App' from project 'App')`,
+/// ending at "(N failures)".
+fn parse_failed_commands(lines: &[&str], start: usize) -> (Vec<BuildFailure>, usize) {
+    let commands = block(lines, start, |line| {
+        let line = line.trim();
+        // The list ends with a count: "(2 failures)"
+        let is_count = line.starts_with('(') && line.contains("failure");
+        !line.is_empty() && !is_count
+    });
+
+    let failures = commands
+        .iter()
+        .map(|command| BuildFailure {
+            task: Some(command.trim().to_string()),
+            ..failure("xcode", &format!("Build command failed: {}", command.trim()), Vec::new())
+        })
+        .collect();
+    (failures, 1 + commands.len())
+}
+
+/// Gradle's "* What went wrong:" block, up to "* Try:". The first line usually
+/// names the task: "Execution failed for task ':app:compileDebugKotlin'."
+fn parse_gradle_block(lines: &[&str], start: usize) -> (Vec<BuildFailure>, usize) {
+    let block = block(lines, start, |line| !line.trim().starts_with("* "));
+    let consumed = 1 + block.len();
+    let excerpt: Vec<String> = block.into_iter().filter(|line| !line.trim().is_empty()).collect();
+
+    let details: Vec<&str> = excerpt
+        .iter()
+        .map(|line| line.trim().trim_start_matches('>').trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let first = match details.first() {
+        Some(first) => *first,
+        None => return (Vec::new(), consumed),
+    };
+
+    let task = first
+        .strip_prefix("Execution failed for task '")
+        .and_then(|rest| rest.split_once('\''))
+        .map(|(task, _)| task.to_string());
+    // "Execution failed for task ..." says where, the next line says why
+    let message = match (&task, details.get(1)) {
+        (Some(_), Some(reason)) => format!("{} {}", first, reason),
+        _ => first.to_string(),
+    };
+
+    (vec![BuildFailure { task, ..failure("gradle", &message, excerpt) }], consumed)
+}
+
+fn is_npm_error(line: &str) -> bool {
+    line.starts_with("npm ERR! ") || line.starts_with("npm error ")
+}
+
+/// Consecutive `npm ERR!` lines. The first is usually just the error code
+/// ("npm ERR! code ERESOLVE"), so the message is the first line after it.
+fn parse_npm_block(lines: &[&str], start: usize) -> (Vec<BuildFailure>, usize) {
+    let group: Vec<&str> = lines[start..]
+        .iter()
+        .map(|line| line.trim())
+        .take_while(|line| is_npm_error(line))
+        .collect();
+    let text = |line: &&str| {
+        line.strip_prefix("npm ERR! ")
+            .or_else(|| line.strip_prefix("npm error "))
+            .unwrap_or(line)
+            .trim()
+            .to_string()
+    };
+
+    let message = group
+        .iter()
+        .map(text)
+        .find(|line| !line.is_empty() && !line.starts_with("code ") && !line.starts_with("errno "))
+        .unwrap_or_else(|| text(&group[0]));
+    let excerpt = group.iter().take(MAX_EXCERPT_LINES).map(|line| line.to_string()).collect();
+    (vec![failure("npm", &message, excerpt)], group.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xcode_failures() {
+        let xcode = "\
+CompileSwift normal arm64 /work/App/ios/App/AppDelegate.swift
+/work/App/ios/App/AppDelegate.swift:12:5: error: cannot find 'foo' in scope
+    foo()
+    ^~~
+\x1b[31mld: library 'Pods-App' not found\x1b[0m
+
+The following build commands failed:
+\tCompileSwift normal arm64 /work/App/ios/App/AppDelegate.swift (in target 'App' from project 'App')
+\tLd /work/Build/App normal (in target 'App' from project 'App')
+(2 failures)
+";
+        let report = analyze_log(xcode, &KnownErrors::builtin());
+        assert!(report.failed);
+        let categories: Vec<&str> = report.failures.iter().map(|f| f.category.as_str()).collect();
+        assert_eq!(categories, ["xcode"; 4]);
+        let swift = &report.failures[0];
+        assert_eq!(swift.file.as_deref(), Some("/work/App/ios/App/AppDelegate.swift"));
+        assert_eq!((swift.line, swift.column), (Some(12), Some(5)));
+        assert_eq!(swift.message, "cannot find 'foo' in scope");
+        assert_eq!(swift.excerpt, ["    foo()", "    ^~~"]);
+        assert_eq!(report.failures[1].message, "ld: library 'Pods-App' not found");
+        assert!(report.failures[2].task.as_deref().unwrap().starts_with("CompileSwift normal arm64"));
+    }
+
+    #[test]
+    fn test_gradle_kotlin_and_java_failures() {
+        let gradle = "\
+> Task :app:compileDebugKotlin FAILED
+e: file:///work/App/android/app/src/main/java/com/app/MainActivity.kt:21:9 Unresolved reference: foo
+e: /work/App/android/app/src/main/java/com/app/Old.kt: (3, 1): Expecting member declaration
+/work/App/android/app/src/main/java/com/app/Module.java:40: error: cannot find symbol
+
+FAILURE: Build failed with an exception.
+
+* What went wrong:
+Execution failed for task ':app:compileDebugKotlin'.
+> Compilation error. See log for more details
+
+* Try:
+> Run with --stacktrace option to get the stack trace.
+";
+        let report = analyze_log(gradle, &KnownErrors::builtin());
+        let summary: Vec<(&str, Option<u32>, Option<u32>)> = report.failures.iter()
+            .map(|f| (f.category.as_str(), f.line, f.column))
+            .collect();
+        assert_eq!(summary, [
+            ("kotlin", Some(21), Some(9)),
+            ("kotlin", Some(3), Some(1)),
+            ("java", Some(40), None),
+            ("gradle", None, None),
+        ]);
+        assert_eq!(report.failures[0].message, "Unresolved reference: foo");
+        let gradle_failure = &report.failures[3];
+        assert_eq!(gradle_failure.task.as_deref(), Some(":app:compileDebugKotlin"));
+        assert_eq!(gradle_failure.message, "Execution failed for task ':app:compileDebugKotlin'. Compilation error. See log for more details");
+    }
+
+    #[test]
+    fn test_cocoapods_and_npm_failures() {
+        let tooling = "\
+[!] CocoaPods could not find compatible versions for pod \"React-Core\":
+  In Podfile:
+    React-Core (from `../node_modules/react-native/`)
+
+npm ERR! code ERESOLVE
+npm ERR! ERESOLVE unable to resolve dependency tree
+npm ERR! Found: react@18.2.0
+";
+        let report = analyze_log(tooling, &KnownErrors::builtin());
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.failures[0].category, "cocoapods");
+        assert_eq!(report.failures[0].excerpt.len(), 2);
+        assert_eq!(report.failures[1].category, "npm");
+        assert_eq!(report.failures[1].message, "ERESOLVE unable to resolve dependency tree");
+    }
+
+    #[test]
+    fn test_successful_build_has_no_failures() {
+        let clean = analyze_log("info Building...\n** BUILD SUCCEEDED **\n", &KnownErrors::builtin());
+        assert!(!clean.failed);
+        assert!(clean.failures.is_empty());
+        assert!(clean.known_error.is_none());
+    }
+
+    #[test]
+    fn test_known_error_in_log() {
+        let report = analyze_log("\
+> Task :app:checkDebugDuplicateClasses FAILED
+Duplicate class android.support.v4.app.INotificationSideChannel found in modules core-1.9.0 and support-compat-28.0.0
+", &KnownErrors::builtin());
+        assert_eq!(report.known_error.map(|known| known.id).as_deref(), Some("duplicate-class"));
+    }
+}
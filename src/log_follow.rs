@@ -0,0 +1,179 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+use crate::args::LogLevel;
+use crate::error::Result;
+use crate::utils::{parse_build_marker, strip_ansi_codes, BuildStatus, LogCleaner};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Which lines of a followed log to print.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Case-insensitive text a line must contain
+    pub grep: Option<String>,
+    pub level: Option<LogLevel>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, line: &str) -> bool {
+        let lower = line.to_lowercase();
+        if let Some(grep) = &self.grep {
+            if !lower.contains(&grep.to_lowercase()) {
+                return false;
+            }
+        }
+        match self.level {
+            None => true,
+            Some(LogLevel::Error) => is_error(line, &lower),
+            Some(LogLevel::Warning) => is_error(line, &lower) || is_warning(line, &lower),
+        }
+    }
+}
+
+/// Compiler diagnostics ("error:", Kotlin's "e: "), failed Gradle tasks,
+/// "BUILD FAILED", CocoaPods "[!]" and "npm ERR!" lines.
+fn is_error(line: &str, lower: &str) -> bool {
+    lower.contains("error:")
+        || lower.contains("fatal error")
+        || line.starts_with("e: ")
+        || line.starts_with("[!] ")
+        || line.starts_with("npm ERR!")
+        || line.contains("FAILED")
+        || line.contains("FAILURE:")
+        || lower.contains("build failed")
+}
+
+fn is_warning(line: &str, lower: &str) -> bool {
+    lower.contains("warning:") || line.starts_with("w: ") || lower.starts_with("warn ")
+}
+
+/// Print the log at `path` line by line as it grows, cleaned like
+/// `logs show` and filtered by `filter`. Stops at the build's result marker
+/// when `until_done` is set, and at the end of the file when `keep_following`
+/// is not. Returns how the build ended, if the log says so.
+pub fn follow(
+    path: &Path,
+    filter: &LogFilter,
+    until_done: bool,
+    keep_following: bool,
+    mut emit: impl FnMut(&str),
+) -> Result<Option<BuildStatus>> {
+    let mut file = File::open(path)?;
+    let mut cleaner = LogCleaner::default();
+    let mut offset = 0;
+    // Bytes after the last newline, waiting for the rest of their line
+    let mut pending: Vec<u8> = Vec::new();
+    let mut status = None;
+
+    loop {
+        // A shorter file was rewritten; start over
+        if file.metadata()?.len() < offset {
+            offset = 0;
+            pending.clear();
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let read = file.read_to_end(&mut pending)?;
+        offset += read as u64;
+
+        let complete = match pending.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => pending.drain(..=end).collect::<Vec<u8>>(),
+            None => Vec::new(),
+        };
+        // Escape sequences never span lines, so whole lines can be stripped on their own
+        let text = strip_ansi_codes(&String::from_utf8_lossy(&complete));
+        for line in text.lines() {
+            if let Some(marker) = parse_build_marker(line) {
+                status = Some(marker);
+                emit(line.trim());
+                if until_done {
+                    return Ok(status);
+                }
+                continue;
+            }
+            if let Some(line) = cleaner.clean_line(line) {
+                if filter.matches(&line) {
+                    emit(&line);
+                }
+            }
+        }
+
+        if read == 0 && !keep_following {
+            return Ok(status);
+        }
+        if read == 0 {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::test_support::TempDir;
+
+    /// A finished Android build log with a warning and a Kotlin error.
+    fn failed_build_log(dir: &TempDir) -> std::path::PathBuf {
+        dir.write("build.log", "=== rn-run android build log ===\n\x1b[32minfo\x1b[0m Building...\n\
+            - Installing...\nwarning: unused variable 'x'\ne: file:///app/Main.kt:3:1 Unresolved reference: foo\n\
+            > Task :app:compileDebugKotlin FAILED\n=== rn-run build failed (exit code 1) ===\nnot reached\n")
+    }
+
+    #[test]
+    fn test_follow_until_build_finishes() {
+        let dir = TempDir::new("follow");
+        let path = dir.write("build.log", "=== rn-run android build log ===\n\x1b[32minfo\x1b[0m Building...\n");
+
+        let writer_path = path.clone();
+        let writer = std::thread::spawn(move || {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&writer_path).unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+            // A line split across writes is only printed once it is complete
+            file.write_all(b"- Installing..\n- Installing...\nwarning: unused variable 'x'\ne: file:///app/Main.kt:3:1 Unre").unwrap();
+            std::thread::sleep(Duration::from_millis(300));
+            file.write_all(b"solved reference: foo\n- Installing...\n> Task :app:compileDebugKotlin FAILED\n").unwrap();
+            file.write_all(b"=== rn-run build failed (exit code 1) ===\nnot reached\n").unwrap();
+        });
+
+        let mut lines = Vec::new();
+        let status = follow(&path, &LogFilter::default(), true, true, |line| lines.push(line.to_string())).unwrap();
+        writer.join().unwrap();
+        assert_eq!(status, Some(BuildStatus::Failed(Some(1))));
+        assert_eq!(lines, [
+            "=== rn-run android build log ===",
+            "info Building...",
+            "- Installing...",
+            "warning: unused variable 'x'",
+            "e: file:///app/Main.kt:3:1 Unresolved reference: foo",
+            "> Task :app:compileDebugKotlin FAILED",
+            "=== rn-run build failed (exit code 1) ===",
+        ]);
+    }
+
+    #[test]
+    fn test_level_filter_keeps_result_marker() {
+        let dir = TempDir::new("follow");
+        let path = failed_build_log(&dir);
+        let errors = LogFilter { grep: None, level: Some(LogLevel::Error) };
+        let mut lines = Vec::new();
+        follow(&path, &errors, false, false, |line| lines.push(line.to_string())).unwrap();
+        assert_eq!(lines, [
+            "e: file:///app/Main.kt:3:1 Unresolved reference: foo",
+            "> Task :app:compileDebugKotlin FAILED",
+            "=== rn-run build failed (exit code 1) ===",
+        ]);
+    }
+
+    #[test]
+    fn test_grep_ignores_case() {
+        let dir = TempDir::new("follow");
+        let path = failed_build_log(&dir);
+        let grep = LogFilter { grep: Some("UNUSED".to_string()), level: Some(LogLevel::Warning) };
+        let mut lines = Vec::new();
+        let status = follow(&path, &grep, false, false, |line| lines.push(line.to_string())).unwrap();
+        assert_eq!(lines, ["warning: unused variable 'x'", "=== rn-run build failed (exit code 1) ==="]);
+        assert_eq!(status, Some(BuildStatus::Failed(Some(1))));
+    }
+}
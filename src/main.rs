@@ -6,79 +6,106 @@ mod main_test;
 mod error;
 mod output;
 mod diagnostics;
+mod terminal;
+mod runner;
+mod package_manager;
+mod version;
+mod config;
+mod cleanup;
+mod dry_run;
+mod metro;
+mod process;
+mod simulator;
+mod emulator;
+mod log_analyzer;
+mod known_errors;
+mod log_follow;
+#[cfg(test)]
+mod test_support;
 
 use std::path::Path;
-use std::process::Command;
 use std::fs;
 
 use clap::Parser;
-use args::Args;
+use args::{Args, Command, DevicesCommand, EnvCommand, LogsCommand, MetroCommand, Platform, RunTarget};
 use ios::run_ios;
 use android::run_android;
-use utils::{get_current_directory, get_react_native_version, is_version_greater_or_equal, list_logs, get_latest_log, get_log_dir, clean_log_content};
+use utils::{BuildStatus, LogStatus, LogWriter, RunOutcome, filter_logs, find_log, select_log, slice_lines, format_bytes, get_current_directory, get_current_directory_logged, list_logs, get_latest_log, get_log_dir, clean_log_content};
 use error::{AppError, Result};
-use output::{Output, ActionResult, RnVersionResult, ScreenshotResult, UpdateResultOutput, LogListResult, LogEntryOutput, LogContentResult};
-use diagnostics::{check_environment, list_simulators, list_emulators};
+use output::{Output, ActionResult, LaunchResult, ConfigResult, DeviceListResult, EmulatorListResult, IosListResult, RnVersionResult, ScreenshotResult, UpdateResultOutput, LogListResult, LogEntryOutput, LogContentResult, LogAnalysisResult, LogFollowResult, BuildFailureReport, KnownErrorListResult, MetroStatusResult};
+use diagnostics::{check_environment, list_ios_devices, list_simulators, list_emulators};
+use runner::{CommandRunner, SystemRunner};
+use package_manager::{current_package_manager, resolve_package_manager, PackageManager};
+use version::resolve_react_native_version;
+use config::Config;
+use cleanup::CleanupPlan;
+use dry_run::DryRunRunner;
+use simulator::IosDeviceOutcome;
+use emulator::AndroidDeviceOutcome;
+use known_errors::{KnownError, KnownErrors};
 
 fn main() {
     let args = Args::parse();
+    if let Err(e) = args.validate() {
+        e.exit();
+    }
+    let runner = SystemRunner;
 
-    if let Err(e) = run(&args) {
-        if args.json {
-            Output::<()>::error("error", &e.to_string(), get_error_fix(&e).as_deref()).print();
-        } else {
-            eprintln!("Error: {}", e);
+    match run(&args, &runner) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            if args.json {
+                Output::<()>::error("error", &e.to_string(), e.fix().as_deref()).print();
+            } else {
+                eprintln!("Error: {}", e);
+            }
+            std::process::exit(1);
         }
-        std::process::exit(1);
     }
 }
 
-fn run(args: &Args) -> Result<()> {
-    // ═══════════════════════════════════════════════════════════════════════════
-    // STANDALONE DIAGNOSTIC COMMANDS (don't require RN project)
-    // ═══════════════════════════════════════════════════════════════════════════
+fn run(args: &Args, runner: &dyn CommandRunner) -> Result<i32> {
+    let project_dir = std::env::current_dir().map_err(|_| AppError::CurrentDir)?;
+    let (config, config_sources) = Config::load(&project_dir)?;
+    let config = config.apply_args(args);
 
-    if args.check_env {
-        let result = check_environment();
-        if args.json {
-            Output::success("check-env", result).print();
-        } else {
-            print_env_check_human(&result);
-        }
-        return Ok(());
+    if args.dry_run {
+        return run_dry_run(args, runner, config).map(|()| 0);
     }
 
-    if args.list_simulators {
-        let result = list_simulators();
-        if args.json {
-            Output::success("list-simulators", result).print();
-        } else {
-            println!("Available iOS Simulators:");
-            for sim in &result.simulators {
-                println!("  {} ({}) - {}", sim.name, sim.runtime, sim.state);
-            }
-            if result.simulators.is_empty() {
-                println!("  No simulators found. Open Xcode > Settings > Platforms to download simulators.");
-                println!("  Tip: Add a simulator named \"iPhone 15\" as it is the default for rn-run.");
+    if let Some((command, legacy_flag)) = args.resolve_command() {
+        if let Some(flag) = legacy_flag {
+            if !args.json {
+                eprintln!("\x1b[33m[rn-run]: {} is deprecated, use 'rn-run {}'\x1b[0m", flag, command.usage());
             }
         }
-        return Ok(());
+        return run_command(args, runner, config, command);
     }
 
-    if args.list_emulators {
-        let result = list_emulators();
+    // ═══════════════════════════════════════════════════════════════════════════
+    // STANDALONE DIAGNOSTIC COMMANDS (don't require RN project)
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    if args.print_config {
         if args.json {
-            Output::success("list-emulators", result).print();
+            Output::success("print-config", ConfigResult {
+                sources: config_sources,
+                config,
+            }).print();
         } else {
-            println!("Available Android Emulators:");
-            for emu in &result.emulators {
-                println!("  {}", emu);
-            }
-            if result.emulators.is_empty() {
-                println!("  No emulators found. Open Android Studio > Device Manager to create one.");
+            if config_sources.is_empty() {
+                println!("# No config files found, using defaults and CLI flags");
+            } else {
+                for source in &config_sources {
+                    println!("# {}", source);
+                }
             }
+            let contents = toml::to_string_pretty(&config)
+                .map_err(|e| AppError::Config(e.to_string()))?;
+            println!("{}", contents);
         }
-        return Ok(());
+        return Ok(0);
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
@@ -86,53 +113,48 @@ fn run(args: &Args) -> Result<()> {
     // ═══════════════════════════════════════════════════════════════════════════
 
     if args.kill_metro {
-        utils::kill_process()?;
+        let port = config.metro_port();
         if args.json {
+            let killed = utils::kill_process_logged(runner, port, Some(&LogWriter::discard()))?;
+            let message = if killed.is_empty() {
+                format!("No Metro running on port {}", port)
+            } else {
+                format!("Stopped {} Metro process(es) on port {}", killed.len(), port)
+            };
             Output::success("kill-metro", ActionResult {
                 action: "kill-metro".to_string(),
-                message: "Metro bundler killed (if running)".to_string(),
+                message,
+                processes: killed,
+                ..Default::default()
             }).print();
         } else {
+            for process in utils::kill_process(runner, port)? {
+                println!("  {} {} ({})", process.pid, process.command, process.signal);
+            }
             println!("\x1b[32m[rn-run]: Metro bundler killed (if running)\x1b[0m");
         }
-        return Ok(());
+        return Ok(0);
     }
 
     if args.quit_simulator {
-        utils::quit_simulator()?;
+        if !terminal::is_macos() {
+            return Err(AppError::UnsupportedPlatform("The iOS Simulator is only available on macOS".to_string()));
+        }
+        utils::quit_simulator(runner)?;
         if args.json {
             Output::success("quit-simulator", ActionResult {
                 action: "quit-simulator".to_string(),
                 message: "iOS Simulator quit".to_string(),
+                ..Default::default()
             }).print();
         } else {
             println!("\x1b[32m[rn-run]: iOS Simulator quit\x1b[0m");
         }
-        return Ok(());
-    }
-
-    if args.screenshot {
-        let output_path = args.output.as_deref();
-        let (platform, path) = if args.android {
-            ("android", utils::take_android_screenshot(output_path)?)
-        } else {
-            ("ios", utils::take_ios_screenshot(output_path)?)
-        };
-
-        if args.json {
-            Output::success("screenshot", ScreenshotResult {
-                platform: platform.to_string(),
-                path: path.clone(),
-                message: format!("Screenshot saved to {}", path),
-            }).print();
-        } else {
-            println!("\x1b[32m[rn-run]: Screenshot saved to {}\x1b[0m", path);
-        }
-        return Ok(());
+        return Ok(0);
     }
 
     if args.update {
-        let result = utils::check_and_update()?;
+        let result = utils::check_and_update(runner)?;
         if args.json {
             Output::success("update", UpdateResultOutput {
                 current_version: result.current_version,
@@ -143,395 +165,773 @@ fn run(args: &Args) -> Result<()> {
         } else {
             println!("\x1b[32m[rn-run]: {}\x1b[0m", result.message);
         }
-        return Ok(());
+        return Ok(0);
     }
 
-    if args.logs {
-        let logs = list_logs()?;
-        let log_dir = get_log_dir();
+    // ═══════════════════════════════════════════════════════════════════════════
+    // TARGETED CLEANUP COMMANDS (don't require full RN detection)
+    // ═══════════════════════════════════════════════════════════════════════════
 
+    if args.delete_simulators {
+        cleanup::delete_simulators(runner)?;
         if args.json {
-            Output::success("logs", LogListResult {
-                log_dir: log_dir.to_string_lossy().to_string(),
-                logs: logs.iter().map(|l| LogEntryOutput {
-                    path: l.path.clone(),
-                    name: l.name.clone(),
-                    size: l.size,
-                    modified: l.modified.clone(),
-                }).collect(),
+            Output::success("delete-simulators", ActionResult {
+                action: "delete-simulators".to_string(),
+                message: "All iOS simulators deleted. Recreate them in Xcode > Settings > Platforms.".to_string(),
+                ..Default::default()
             }).print();
         } else {
-            println!("Build logs ({})", log_dir.to_string_lossy());
-            println!();
-            if logs.is_empty() {
-                println!("  No logs found. Run 'rn-run -i' or 'rn-run -a' to create logs.");
+            println!("\x1b[32m[rn-run]: All iOS simulators deleted\x1b[0m");
+            println!("Recreate simulators in Xcode > Settings > Platforms.");
+        }
+        return Ok(0);
+    }
+
+    if args.delete_emulators {
+        let mut removed = Vec::new();
+        let deletion = cleanup::delete_emulators(runner, &mut removed);
+        let deleted = &deletion.deleted;
+        let bytes_freed: u64 = removed.iter().map(|path| path.bytes).sum();
+
+        // Some AVDs could not be deleted: report those that were, then the failure
+        if let Some(error) = &deletion.error {
+            let message = format!(
+                "Could not delete {} Android emulator(s): {}. Deleted {}: {}, freeing {}.",
+                deletion.failed.len(),
+                deletion.failed.join(", "),
+                deleted.len(),
+                if deleted.is_empty() { "none".to_string() } else { deleted.join(", ") },
+                format_bytes(bytes_freed)
+            );
+            if args.json {
+                Output::failure("delete-emulators", ActionResult {
+                    action: "delete-emulators".to_string(),
+                    message,
+                    removed,
+                    bytes_freed: Some(bytes_freed),
+                    ..Default::default()
+                }, &error.to_string(), error.fix().as_deref()).print();
             } else {
-                for log in &logs {
-                    println!("  {} ({} bytes) - {}", log.name, log.size, log.modified);
+                for name in deleted {
+                    println!("\x1b[32m[rn-run]: Deleted emulator: {}\x1b[0m", name);
+                }
+                eprintln!("\x1b[31m[rn-run]: {}\x1b[0m", message);
+                eprintln!("Error: {}", error);
+                if let Some(fix) = error.fix() {
+                    eprintln!("      Fix: {}", fix);
                 }
-                println!();
-                println!("Use 'rn-run --show-log' to view the most recent log.");
             }
+            return Ok(1);
         }
-        return Ok(());
-    }
-
-    if args.show_log {
-        match get_latest_log()? {
-            Some(log) => {
-                let raw_content = fs::read_to_string(&log.path)
-                    .map_err(|_| AppError::CommandFailed(format!("Failed to read log file: {}", log.path)))?;
 
-                // Clean up duplicate progress lines
-                let content = clean_log_content(&raw_content);
-
-                if args.json {
-                    Output::success("show-log", LogContentResult {
-                        path: log.path,
-                        content,
-                    }).print();
-                } else {
-                    println!("=== {} ===\n", log.name);
-                    println!("{}", content);
-                }
+        if deleted.is_empty() {
+            if args.json {
+                Output::success("delete-emulators", ActionResult {
+                    action: "delete-emulators".to_string(),
+                    message: "No Android emulators to delete.".to_string(),
+                    ..Default::default()
+                }).print();
+            } else {
+                println!("\x1b[32m[rn-run]: No Android emulators to delete\x1b[0m");
             }
-            None => {
-                if args.json {
-                    Output::<()>::error("show-log", "No logs found", Some("Run 'rn-run -i' or 'rn-run -a' to create logs")).print();
-                } else {
-                    println!("No logs found. Run 'rn-run -i' or 'rn-run -a' to create logs.");
-                }
+        } else if args.json {
+            Output::success("delete-emulators", ActionResult {
+                action: "delete-emulators".to_string(),
+                message: format!(
+                    "Deleted {} Android emulator(s): {}, freeing {}. Recreate them in Android Studio > Device Manager.",
+                    deleted.len(),
+                    deleted.join(", "),
+                    format_bytes(bytes_freed)
+                ),
+                removed,
+                bytes_freed: Some(bytes_freed),
+                ..Default::default()
+            }).print();
+        } else {
+            for name in deleted {
+                println!("\x1b[32m[rn-run]: Deleted emulator: {}\x1b[0m", name);
             }
+            println!("\x1b[32m[rn-run]: {} freed\x1b[0m", format_bytes(bytes_freed));
+            println!("Recreate emulators in Android Studio > Device Manager.");
         }
-        return Ok(());
+        return Ok(0);
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
-    // TARGETED CLEANUP COMMANDS (don't require full RN detection)
+    // COMMANDS THAT REQUIRE RN PROJECT CONTEXT
     // ═══════════════════════════════════════════════════════════════════════════
 
-    if args.clean_modules {
-        Command::new("rm")
-            .arg("-rf")
-            .arg("node_modules")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf node_modules".to_string()))?;
+    if args.rn_version {
+        let current_dir = get_current_directory()?;
+        let react_native = resolve_react_native_version(Path::new(&current_dir))?;
+        let react_native_version = &react_native.version;
+        let package_manager = resolve_package_manager(args.package_manager, &config, Path::new(&current_dir), react_native_version);
+
+        let manager = package_manager.manager;
+        let notes = vec![
+            format!("Package manager detected from {}", package_manager.source),
+            format!("Commands run as: {}", manager.exec("react-native")),
+        ];
 
         if args.json {
-            Output::success("clean-modules", ActionResult {
-                action: "clean-modules".to_string(),
-                message: "node_modules deleted. Run npm/yarn install to reinstall.".to_string(),
+            Output::success("rn-version", RnVersionResult {
+                version: react_native_version.to_string(),
+                specifier: react_native.specifier.clone(),
+                source: react_native.source.clone(),
+                package_manager: manager.name().to_string(),
+                notes,
             }).print();
         } else {
-            println!("\x1b[32m[rn-run]: node_modules deleted\x1b[0m");
-            println!("Run 'npm install' or 'yarn install' to reinstall dependencies.");
+            println!("React Native version: {} (from {}, package.json: {})", react_native_version, react_native.source, react_native.specifier);
+            println!("Package manager: {} (from {})", manager.name(), package_manager.source);
         }
-        return Ok(());
+        return Ok(0);
     }
 
-    if args.clean_pods {
-        Command::new("rm").arg("-rf").arg("ios/Pods").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/Pods".to_string()))?;
-        Command::new("rm").arg("-rf").arg("ios/Podfile.lock").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/Podfile.lock".to_string()))?;
-        Command::new("rm").arg("-rf").arg("ios/build").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/build".to_string()))?;
+    if args.json {
+        Output::success("help", ActionResult {
+            action: "help".to_string(),
+            message: "No command specified. Use --help for available options.".to_string(),
+            ..Default::default()
+        }).print();
+    } else {
+        println!("No command specified, use --help for more info");
+    }
+    Ok(0)
+}
 
-        if args.json {
-            Output::success("clean-pods", ActionResult {
-                action: "clean-pods".to_string(),
-                message: "Cleaned ios/Pods, ios/Podfile.lock, ios/build. Run --pod-install to reinstall.".to_string(),
-            }).print();
-        } else {
-            println!("\x1b[32m[rn-run]: ios/Pods deleted\x1b[0m");
-            println!("\x1b[32m[rn-run]: ios/Podfile.lock deleted\x1b[0m");
-            println!("\x1b[32m[rn-run]: ios/build deleted\x1b[0m");
-            println!("Run 'rn-run --pod-install' to reinstall pods.");
+/// Run a subcommand (or the subcommand a deprecated flag maps onto).
+/// Returns the exit status for rn-run, which is the build's own status for a failed foreground build.
+fn run_command(args: &Args, runner: &dyn CommandRunner, config: Config, command: Command) -> Result<i32> {
+    match command {
+        // ═══════════════════════════════════════════════════════════════════════
+        // MAIN RUN COMMANDS
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Run { target } => {
+            let outcome = run_target(args, runner, config, &target)?;
+            report_build(args, target.platform().name(), outcome)
         }
-        return Ok(());
-    }
 
-    if args.clean_gradle {
-        // Stop Gradle daemons first
-        let gradlew = std::path::Path::new("android/gradlew");
-        if gradlew.exists() {
-            if !args.json {
-                println!("\x1b[33m[rn-run]: stopping Gradle daemons...\x1b[0m");
+        // ═══════════════════════════════════════════════════════════════════════
+        // DIAGNOSTICS
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Env { command: EnvCommand::Check } => {
+            let result = check_environment(runner);
+            if args.json {
+                Output::success("check-env", result).print();
+            } else {
+                print_env_check_human(&result);
             }
-            let _ = Command::new("./android/gradlew")
-                .args(["--stop"])
-                .current_dir(".")
-                .status();
-        }
-
-        Command::new("rm").arg("-rf").arg("android/build").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/build".to_string()))?;
-        Command::new("rm").arg("-rf").arg("android/app/build").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/app/build".to_string()))?;
-        Command::new("rm").arg("-rf").arg("android/.gradle").status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/.gradle".to_string()))?;
-
-        // Clean version-specific global Gradle cache
-        let mut global_cache_msg = String::new();
-        let wrapper_props = std::path::Path::new("android/gradle/wrapper/gradle-wrapper.properties");
-        if let Ok(contents) = std::fs::read_to_string(wrapper_props) {
-            // Parse version from distributionUrl, e.g. gradle-8.8-all.zip or gradle-8.8-bin.zip
-            if let Some(version) = contents.lines()
-                .find(|l| l.starts_with("distributionUrl"))
-                .and_then(|l| {
-                    let re_start = l.find("gradle-")?;
-                    let after = &l[re_start + 7..];
-                    let end = after.find('-')?;
-                    Some(after[..end].to_string())
-                })
-            {
-                if let Ok(home) = std::env::var("HOME") {
-                    let cache_path = std::path::PathBuf::from(&home).join(".gradle").join("caches").join(&version);
-                    if cache_path.exists() {
-                        Command::new("rm").arg("-rf").arg(&cache_path).status()
-                            .map_err(|_| AppError::CommandFailed(format!("rm -rf ~/.gradle/caches/{}", version)))?;
-                        global_cache_msg = format!(", ~/.gradle/caches/{}", version);
+            Ok(0)
+        }
+
+        Command::Devices { command: DevicesCommand::List { platform } } => {
+            match platform {
+                Some(Platform::Ios) => {
+                    let result = IosListResult {
+                        simulators: list_simulators(runner).simulators,
+                        devices: list_ios_devices(runner),
+                    };
+                    if args.json {
+                        Output::success("list-simulators", result).print();
+                    } else {
+                        print_simulators_human(&result);
+                    }
+                }
+                Some(Platform::Android) => {
+                    let result = list_emulators(runner);
+                    if args.json {
+                        Output::success("list-emulators", result).print();
+                    } else {
+                        print_emulators_human(&result);
+                    }
+                }
+                None => {
+                    let android = list_emulators(runner);
+                    let result = DeviceListResult {
+                        simulators: list_simulators(runner).simulators,
+                        ios_devices: list_ios_devices(runner),
+                        emulators: android.emulators,
+                        avds: android.avds,
+                        android_devices: android.devices,
+                    };
+                    if args.json {
+                        Output::success("list-devices", result).print();
+                    } else {
+                        print_simulators_human(&IosListResult {
+                            simulators: result.simulators,
+                            devices: result.ios_devices,
+                        });
+                        println!();
+                        print_emulators_human(&EmulatorListResult {
+                            emulators: result.emulators,
+                            avds: result.avds,
+                            devices: result.android_devices,
+                        });
                     }
                 }
             }
+            Ok(0)
         }
 
-        if args.json {
-            Output::success("clean-gradle", ActionResult {
-                action: "clean-gradle".to_string(),
-                message: format!("Stopped Gradle daemons. Cleaned android/build, android/app/build, android/.gradle{}", global_cache_msg),
-            }).print();
-        } else {
-            println!("\x1b[32m[rn-run]: Gradle daemons stopped\x1b[0m");
-            println!("\x1b[32m[rn-run]: android/build deleted\x1b[0m");
-            println!("\x1b[32m[rn-run]: android/app/build deleted\x1b[0m");
-            println!("\x1b[32m[rn-run]: android/.gradle deleted\x1b[0m");
-            if !global_cache_msg.is_empty() {
-                println!("\x1b[32m[rn-run]:{} deleted\x1b[0m", global_cache_msg);
+        Command::Devices { command: DevicesCommand::Ios { command } } => {
+            match simulator::execute(runner, &config, &command)? {
+                IosDeviceOutcome::Action(result) => {
+                    if args.json {
+                        let action = format!("devices-ios-{}", result.action);
+                        Output::success(&action, result).print();
+                    } else {
+                        println!("\x1b[32m[rn-run]: {}\x1b[0m", result.message);
+                    }
+                }
+                IosDeviceOutcome::Provision(result) => {
+                    if args.json {
+                        Output::success("devices-ios-provision", result).print();
+                    } else {
+                        for sim in &result.created {
+                            println!("  created  {} ({}, {})", sim.name, simulator::runtime_label(&sim.runtime), sim.udid);
+                        }
+                        for sim in &result.existing {
+                            println!("  exists   {} ({}, {})", sim.name, simulator::runtime_label(&sim.runtime), sim.udid);
+                        }
+                        println!("\x1b[32m[rn-run]: {}\x1b[0m", result.message);
+                    }
+                }
             }
+            Ok(0)
         }
-        return Ok(());
-    }
 
-    if args.clean_metro {
-        let tmpdir = std::env::var("TMPDIR").unwrap_or_else(|_| "/tmp".to_string());
-        Command::new("sh")
-            .arg("-c")
-            .arg(format!("rm -rf {}metro-* {}haste-map-*", tmpdir, tmpdir))
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf metro cache".to_string()))?;
-
-        if args.json {
-            Output::success("clean-metro", ActionResult {
-                action: "clean-metro".to_string(),
-                message: "Metro cache cleared".to_string(),
-            }).print();
-        } else {
-            println!("\x1b[32m[rn-run]: Metro cache cleared\x1b[0m");
+        Command::Devices { command: DevicesCommand::Android { command } } => {
+            match emulator::execute(runner, &config, &command)? {
+                AndroidDeviceOutcome::Emulator(result) => {
+                    if args.json {
+                        let action = format!("devices-android-{}", result.action);
+                        Output::success(&action, result).print();
+                    } else {
+                        println!("\x1b[32m[rn-run]: {}\x1b[0m", result.message);
+                    }
+                }
+                AndroidDeviceOutcome::Reverse(result) => {
+                    if args.json {
+                        Output::success("devices-android-reverse", result).print();
+                    } else {
+                        println!("\x1b[32m[rn-run]: {}\x1b[0m", result.message);
+                    }
+                }
+            }
+            Ok(0)
         }
-        return Ok(());
-    }
 
-    if args.delete_simulators {
-        let status = Command::new("xcrun")
-            .args(["simctl", "delete", "all"])
-            .status()
-            .map_err(|_| AppError::CommandFailed("xcrun simctl delete all".to_string()))?;
+        // ═══════════════════════════════════════════════════════════════════════
+        // LOGS
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Logs { command: LogsCommand::List { filter } } => {
+            let logs = filter_logs(list_logs()?, &filter)?;
+            let log_dir = get_log_dir();
 
-        if status.success() {
             if args.json {
-                Output::success("delete-simulators", ActionResult {
-                    action: "delete-simulators".to_string(),
-                    message: "All iOS simulators deleted. Recreate them in Xcode > Settings > Platforms.".to_string(),
+                Output::success("logs", LogListResult {
+                    log_dir: log_dir.to_string_lossy().to_string(),
+                    logs: logs.iter().enumerate().map(|(i, l)| LogEntryOutput {
+                        index: i + 1,
+                        path: l.path.clone(),
+                        name: l.name.clone(),
+                        size: l.size,
+                        modified: l.modified.clone(),
+                        project: l.metadata.as_ref().map(|m| m.project.clone()),
+                        platform: l.platform().map(str::to_string),
+                        status: l.status,
+                        metadata: l.metadata.clone(),
+                    }).collect(),
                 }).print();
             } else {
-                println!("\x1b[32m[rn-run]: All iOS simulators deleted\x1b[0m");
-                println!("Recreate simulators in Xcode > Settings > Platforms.");
+                println!("Build logs ({})", log_dir.to_string_lossy());
+                println!();
+                if logs.is_empty() {
+                    println!("  No logs found. Run 'rn-run run ios' or 'rn-run run android' to create logs.");
+                } else {
+                    for (index, log) in logs.iter().enumerate() {
+                        let project = log.metadata.as_ref().map(|m| format!("[{}] ", m.project)).unwrap_or_default();
+                        println!("  {:>2}. {}{} ({} bytes) - {} - {}", index + 1, project, log.name, log.size, log.modified, log.status.name());
+                    }
+                    println!();
+                    println!("Use 'rn-run logs show' to view the most recent log, or 'rn-run logs show <number>' for another.");
+                }
             }
-        } else {
-            return Err(AppError::CommandFailed("xcrun simctl delete all failed".to_string()));
+            Ok(0)
         }
-        return Ok(());
-    }
 
-    if args.delete_emulators {
-        // Kill any running emulators first by finding all emulator devices
-        let mut killed_any = false;
-        if let Ok(output) = Command::new("adb").args(["devices"]).output() {
-            let devices = String::from_utf8_lossy(&output.stdout);
-            for line in devices.lines() {
-                if line.starts_with("emulator-") {
-                    if let Some(device_id) = line.split_whitespace().next() {
-                        let _ = Command::new("adb")
-                            .args(["-s", device_id, "emu", "kill"])
-                            .output();
-                        killed_any = true;
+        Command::Logs { command: LogsCommand::Show(show) } => {
+            let mut filter = show.filter.clone();
+            if show.failed {
+                filter.status = Some(LogStatus::Failed);
+            }
+
+            match select_log(show.log.as_deref(), &filter)? {
+                Some(log) => {
+                    let raw_content = fs::read_to_string(&log.path)
+                        .map_err(|_| AppError::CommandFailed(format!("Failed to read log file: {}", log.path)))?;
+
+                    // Clean up duplicate progress lines
+                    let content = if show.raw { raw_content.clone() } else { clean_log_content(&raw_content) };
+                    let content = slice_lines(&content, show.head, show.tail);
+
+                    if args.json {
+                        Output::success("show-log", LogContentResult {
+                            path: log.path,
+                            name: log.name,
+                            status: log.status,
+                            metadata: log.metadata,
+                            raw: show.raw,
+                            analysis: log_analyzer::analyze_log(&raw_content, KnownErrors::shared()),
+                            content,
+                        }).print();
+                    } else {
+                        println!("=== {} ===\n", log.name);
+                        println!("{}", content);
+                    }
+                }
+                None => {
+                    if args.json {
+                        Output::<()>::error("show-log", "No logs found", Some("Run 'rn-run run ios' or 'rn-run run android' to create logs")).print();
+                    } else {
+                        println!("No logs found. Run 'rn-run run ios' or 'rn-run run android' to create logs.");
                     }
                 }
             }
+            Ok(0)
         }
-        // Wait for emulators to shut down
-        if killed_any {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+
+        Command::Logs { command: LogsCommand::Analyze } => {
+            let log = get_latest_log()?.ok_or_else(|| AppError::CommandFailed(
+                "No logs found; run 'rn-run run ios' or 'rn-run run android' to create one".to_string(),
+            ))?;
+            let content = fs::read_to_string(&log.path)
+                .map_err(|_| AppError::CommandFailed(format!("Failed to read log file: {}", log.path)))?;
+            let analysis = log_analyzer::analyze_log(&content, KnownErrors::shared());
+
+            if args.json {
+                Output::success("logs-analyze", LogAnalysisResult {
+                    path: log.path,
+                    analysis,
+                }).print();
+            } else {
+                println!("=== {} ===\n", log.name);
+                print_build_failures_human(&analysis);
+            }
+            Ok(0)
         }
 
-        let emulators = list_emulators();
-        if emulators.emulators.is_empty() {
+        // ═══════════════════════════════════════════════════════════════════════
+        // METRO
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Metro { command } => {
+            let port = config.metro_port();
+            let message = match command {
+                MetroCommand::Status => {
+                    let result = metro::metro_status(runner, port);
+                    if args.json {
+                        Output::success("metro-status", result).print();
+                    } else {
+                        print_metro_status_human(&result);
+                    }
+                    return Ok(0);
+                }
+                MetroCommand::Reload => {
+                    metro::reload(port)?;
+                    format!("Reloaded apps connected to Metro on port {}", port)
+                }
+                MetroCommand::DevMenu => {
+                    metro::open_dev_menu(port)?;
+                    format!("Opened the dev menu in apps connected to Metro on port {}", port)
+                }
+                MetroCommand::Debugger => {
+                    metro::open_debugger(port)?;
+                    format!("Opened the debugger via Metro on port {}", port)
+                }
+            };
+
+            let action = format!("metro-{}", command.name());
             if args.json {
-                Output::success("delete-emulators", ActionResult {
-                    action: "delete-emulators".to_string(),
-                    message: "No Android emulators to delete.".to_string(),
+                Output::success(&action, ActionResult {
+                    action: action.clone(),
+                    message,
+                    ..Default::default()
                 }).print();
             } else {
-                println!("\x1b[32m[rn-run]: No Android emulators to delete\x1b[0m");
+                println!("\x1b[32m[rn-run]: {}\x1b[0m", message);
             }
-        } else {
-            // Get the AVD directory path (~/.android/avd/)
-            let home = std::env::var("HOME").unwrap_or_default();
-            let avd_dir = format!("{}/.android/avd", home);
-
-            let mut deleted = Vec::new();
-            let mut failed = Vec::new();
-
-            for name in &emulators.emulators {
-                let avd_folder = format!("{}/{}.avd", avd_dir, name);
-                let avd_ini = format!("{}/{}.ini", avd_dir, name);
-
-                // Delete the .avd directory
-                let dir_result = Command::new("rm")
-                    .args(["-rf", &avd_folder])
-                    .status();
-
-                // Delete the .ini file
-                let ini_result = Command::new("rm")
-                    .args(["-f", &avd_ini])
-                    .status();
-
-                match (dir_result, ini_result) {
-                    (Ok(d), Ok(i)) if d.success() && i.success() => deleted.push(name.clone()),
-                    _ => failed.push(name.clone()),
+            Ok(0)
+        }
+
+        Command::Logs { command: LogsCommand::Follow { log, grep, level, until_done } } => {
+            let log = match log {
+                Some(name) => find_log(&name)?,
+                None => get_latest_log()?.ok_or_else(|| AppError::LogNotFound("(most recent)".to_string()))?,
+            };
+            let filter = log_follow::LogFilter { grep, level };
+
+            // JSON output is a single object, so without --until-done it reports the log as it is now
+            let status = if args.json {
+                let mut lines = Vec::new();
+                let status = log_follow::follow(Path::new(&log.path), &filter, until_done, until_done, |line| {
+                    lines.push(line.to_string())
+                })?;
+                let (success, exit_code) = match status {
+                    Some(BuildStatus::Succeeded) => (Some(true), Some(0)),
+                    Some(BuildStatus::Failed(code)) => (Some(false), code),
+                    None => (None, None),
+                };
+                Output::success("logs-follow", LogFollowResult {
+                    path: log.path.clone(),
+                    done: status.is_some(),
+                    success,
+                    exit_code,
+                    lines,
+                }).print();
+                status
+            } else {
+                eprintln!("=== {} ===\n", log.name);
+                log_follow::follow(Path::new(&log.path), &filter, until_done, true, |line| println!("{}", line))?
+            };
+
+            if until_done {
+                if let Some(BuildStatus::Failed(code)) = status {
+                    std::process::exit(code.filter(|code| *code != 0).unwrap_or(1));
                 }
             }
+            Ok(0)
+        }
+
+        // ═══════════════════════════════════════════════════════════════════════
+        // EXPLAIN
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Explain { id: Some(id) } => {
+            let known = KnownErrors::load()?;
+            let error = known.find(&id).ok_or_else(|| AppError::UnknownErrorId(id.clone()))?;
 
-            if !failed.is_empty() {
-                return Err(AppError::CommandFailed(format!(
-                    "Failed to delete emulators: {}",
-                    failed.join(", ")
-                )));
+            if args.json {
+                Output::success("explain", error).print();
+            } else {
+                print_known_error_human(error);
             }
+            Ok(0)
+        }
+
+        Command::Explain { id: None } => {
+            let known = KnownErrors::load()?;
+            let user_file = known_errors::user_known_errors_path();
 
             if args.json {
-                Output::success("delete-emulators", ActionResult {
-                    action: "delete-emulators".to_string(),
-                    message: format!(
-                        "Deleted {} Android emulator(s): {}. Recreate them in Android Studio > Device Manager.",
-                        deleted.len(),
-                        deleted.join(", ")
-                    ),
+                Output::success("explain", KnownErrorListResult {
+                    user_file: user_file.map(|path| path.to_string_lossy().to_string()),
+                    errors: known.errors,
                 }).print();
             } else {
-                for name in &deleted {
-                    println!("\x1b[32m[rn-run]: Deleted emulator: {}\x1b[0m", name);
+                println!("Known build errors:\n");
+                for error in &known.errors {
+                    println!("  {:<24} {}", error.id, error.title);
+                }
+                println!("\nUse 'rn-run explain <id>' for details.");
+                if let Some(path) = user_file {
+                    println!("Add your own in {}", path.to_string_lossy());
                 }
-                println!("Recreate emulators in Android Studio > Device Manager.");
             }
+            Ok(0)
         }
-        return Ok(());
-    }
 
-    if args.pod_install {
-        let status = Command::new("sh")
-            .arg("-c")
-            .arg("cd ios && pod install")
-            .status()
-            .map_err(|_| AppError::CommandFailed("pod install".to_string()))?;
+        Command::Screenshot { platform, output } => {
+            let output_path = output.as_deref();
+            let path = match platform {
+                Platform::Ios => utils::take_ios_screenshot(runner, output_path)?,
+                Platform::Android => utils::take_android_screenshot(runner, output_path)?,
+            };
 
-        if status.success() {
             if args.json {
-                Output::success("pod-install", ActionResult {
-                    action: "pod-install".to_string(),
-                    message: "pod install completed successfully".to_string(),
+                Output::success("screenshot", ScreenshotResult {
+                    platform: platform.name().to_string(),
+                    path: path.clone(),
+                    message: format!("Screenshot saved to {}", path),
                 }).print();
             } else {
-                println!("\x1b[32m[rn-run]: pod install completed\x1b[0m");
+                println!("\x1b[32m[rn-run]: Screenshot saved to {}\x1b[0m", path);
             }
-        } else {
-            return Err(AppError::CommandFailed("pod install failed".to_string()));
+            Ok(0)
+        }
+
+        // ═══════════════════════════════════════════════════════════════════════
+        // TARGETED CLEANUP COMMANDS (don't require full RN detection)
+        // ═══════════════════════════════════════════════════════════════════════
+
+        Command::Clean { targets, pod_install } => {
+            let plan = CleanupPlan::from_targets(&targets, pod_install, cleanup_package_manager(args));
+            run_cleanup(args, runner, &plan).map(|()| 0)
         }
-        return Ok(());
     }
+}
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // COMMANDS THAT REQUIRE RN PROJECT CONTEXT
-    // ═══════════════════════════════════════════════════════════════════════════
+/// Package manager that reinstalls node_modules for `rn-run clean`: --package-manager,
+/// else the project's, else npm outside a React Native project.
+fn cleanup_package_manager(args: &Args) -> PackageManager {
+    args.package_manager.or_else(current_package_manager).unwrap_or(PackageManager::Npm)
+}
 
-    let current_dir = get_current_directory()?;
-    let package_json_path = Path::new(&current_dir).join("package.json");
+/// Resolve the project context and run the app for `target`.
+fn run_target(args: &Args, runner: &dyn CommandRunner, config: Config, target: &RunTarget) -> Result<RunOutcome> {
+    let config = config.apply_run(target);
+    let build = target.build();
 
-    let react_native_version = match get_react_native_version(&package_json_path)? {
-        Some(version) => version,
-        None => return Err(AppError::ReactNativeNotFound),
+    // Keep stdout clean for the JSON result
+    let current_dir = if args.json {
+        get_current_directory_logged(Some(&LogWriter::discard()))?
+    } else {
+        get_current_directory()?
     };
+    let react_native = resolve_react_native_version(Path::new(&current_dir))?;
+    let react_native_version = &react_native.version;
+    let package_manager = resolve_package_manager(
+        build.package_manager.or(args.package_manager),
+        &config,
+        Path::new(&current_dir),
+        react_native_version,
+    );
+
+    match target.platform() {
+        Platform::Ios => run_ios(runner, &config, build, args.json, &current_dir, react_native_version, package_manager.manager),
+        Platform::Android => run_android(runner, &config, build, args.json, &current_dir, react_native_version, package_manager.manager),
+    }
+}
 
-    // RN Version command
-    if args.rn_version {
-        let package_manager = if is_version_greater_or_equal(&react_native_version, "0.74") {
-            "npm"
-        } else {
-            "yarn"
-        };
+/// Walk a destructive command with a `DryRunRunner` and print what it would
+/// delete, kill and execute instead of its normal output.
+fn run_dry_run(args: &Args, runner: &dyn CommandRunner, config: Config) -> Result<()> {
+    let dry_runner = DryRunRunner::new(runner);
+    let quiet = LogWriter::discard();
+
+    let description = match args.resolve_command() {
+        Some((command, _)) => {
+            match &command {
+                Command::Run { target } => {
+                    run_target(args, &dry_runner, config, target)?;
+                }
+                Command::Clean { targets, pod_install } => {
+                    CleanupPlan::from_targets(targets, *pod_install, cleanup_package_manager(args)).execute(&dry_runner, true);
+                }
+                Command::Devices { command: DevicesCommand::Ios { command } } => {
+                    simulator::execute(&dry_runner, &config, command)?;
+                }
+                Command::Devices { command: DevicesCommand::Android { command } } => {
+                    emulator::execute(&dry_runner, &config, command)?;
+                }
+                _ => return Err(AppError::DryRunUnsupported(format!("'{}'", command.usage()))),
+            }
+            command.usage()
+        }
+        None if args.kill_metro => {
+            utils::kill_process_logged(&dry_runner, config.metro_port(), Some(&quiet))?;
+            "--kill-metro".to_string()
+        }
+        None if args.quit_simulator => {
+            utils::quit_simulator(&dry_runner)?;
+            "--quit-simulator".to_string()
+        }
+        None if args.delete_simulators => {
+            cleanup::delete_simulators(&dry_runner)?;
+            "--delete-simulators".to_string()
+        }
+        None if args.delete_emulators => {
+            if let Some(error) = cleanup::delete_emulators(&dry_runner, &mut Vec::new()).error {
+                return Err(error);
+            }
+            "--delete-emulators".to_string()
+        }
+        None => return Err(AppError::DryRunUnsupported("this command".to_string())),
+    };
 
-        let mut notes = Vec::new();
-        if is_version_greater_or_equal(&react_native_version, "0.74") {
-            notes.push("RN 0.74+ uses npm and npx for commands".to_string());
-        } else {
-            notes.push("RN < 0.74 uses yarn for commands".to_string());
+    let result = dry_runner.report(&description);
+    if args.json {
+        Output::success("dry-run", result).print();
+        return Ok(());
+    }
+
+    println!("Dry run: rn-run {}\n", result.command);
+    if !result.deletions.is_empty() {
+        println!("Would delete ({}):", format_bytes(result.total_bytes));
+        for deletion in &result.deletions {
+            println!("  {} ({})", deletion.path, format_bytes(deletion.bytes));
         }
+        println!();
+    }
+    if !result.processes.is_empty() {
+        println!("Would kill:");
+        for process in &result.processes {
+            match process.pid {
+                Some(pid) => println!("  {} {}", pid, process.process),
+                None => println!("  {}", process.process),
+            }
+        }
+        println!();
+    }
+    if !result.commands.is_empty() {
+        println!("Would run:");
+        for command in &result.commands {
+            println!("  {}", command);
+        }
+        println!();
+    }
+    println!("\x1b[33m[rn-run]: {}\x1b[0m", result.message);
+    Ok(())
+}
 
-        if args.json {
-            Output::success("rn-version", RnVersionResult {
-                version: react_native_version,
-                package_manager: package_manager.to_string(),
-                notes,
-            }).print();
+/// Run the cleanup plan and report every step in one result.
+/// Exits with status 1 when any step failed or was skipped.
+fn run_cleanup(args: &Args, runner: &dyn CommandRunner, plan: &CleanupPlan) -> Result<()> {
+    let result = plan.execute(runner, args.json);
+    let command = plan.command_name();
+
+    if args.json {
+        if result.success {
+            Output::success(command, result).print();
         } else {
-            println!("React Native version: {}", react_native_version);
-            println!("Recommended package manager: {}", package_manager);
+            let fix = result.steps.iter().find_map(|step| step.fix.clone());
+            let message = result.message.clone();
+            Output::failure(command, result, &message, fix.as_deref()).print();
+            std::process::exit(1);
         }
         return Ok(());
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // MAIN RUN COMMANDS
-    // ═══════════════════════════════════════════════════════════════════════════
-
-    match args {
-        Args { ios: true, android: false, .. } => run_ios(args, &current_dir, &react_native_version),
-        Args { android: true, ios: false, .. } => run_android(args, &current_dir, &react_native_version),
-        Args { ios: true, android: true, .. } => {
-            if args.json {
-                Output::<()>::error(
-                    "error",
-                    "Cannot run both iOS and Android simultaneously",
-                    Some("Use either --ios or --android, not both")
-                ).print();
-            } else {
-                eprintln!("Cannot run both iOS and Android simultaneously");
+    for step in &result.steps {
+        let duration_secs = step.duration_ms as f64 / 1000.0;
+        match (step.status.as_str(), &step.message, &step.error) {
+            ("ok", Some(message), _) => match step.bytes_freed {
+                Some(bytes) => println!("\x1b[32m[rn-run]: ✓ {} ({:.1}s): {} ({} freed)\x1b[0m", step.action, duration_secs, message, format_bytes(bytes)),
+                None => println!("\x1b[32m[rn-run]: ✓ {} ({:.1}s): {}\x1b[0m", step.action, duration_secs, message),
+            },
+            (status, _, error) => {
+                eprintln!("\x1b[31m[rn-run]: ✗ {} {}: {}\x1b[0m", step.action, status, error.as_deref().unwrap_or(""));
+                if let Some(fix) = &step.fix {
+                    eprintln!("      Fix: {}", fix);
+                }
             }
-            std::process::exit(1);
         }
-        _ => {
+    }
+
+    if result.success {
+        println!("\x1b[32m[rn-run]: {} ({} freed)\x1b[0m", result.message, format_bytes(result.bytes_freed));
+        Ok(())
+    } else {
+        eprintln!("\x1b[31m[rn-run]: {}\x1b[0m", result.message);
+        std::process::exit(1);
+    }
+}
+
+/// Report the result of a foreground build and return the exit status for rn-run:
+/// the build's own status on failure. Builds launched in a new terminal window
+/// only report that they were started.
+fn report_build(args: &Args, platform: &str, outcome: RunOutcome) -> Result<i32> {
+    let command = format!("run-{}", platform);
+    let outcome = match outcome.build {
+        Some(build) => build,
+        None => {
             if args.json {
-                Output::success("help", ActionResult {
-                    action: "help".to_string(),
-                    message: "No command specified. Use --help for available options.".to_string(),
+                Output::success(&command, LaunchResult {
+                    platform: platform.to_string(),
+                    metro: outcome.metro,
+                    message: "Build started in a new terminal window".to_string(),
                 }).print();
-            } else {
-                println!("No platform specified, use --help for more info");
             }
-            Ok(())
+            return Ok(0);
+        }
+    };
+
+    let result = outcome.result(platform);
+    let message = result.message.clone();
+
+    // Match a failed build's log against the known errors catalogue
+    let known = if outcome.success {
+        None
+    } else {
+        fs::read_to_string(&outcome.log_path)
+            .ok()
+            .and_then(|log| KnownErrors::shared().match_text(&log).cloned())
+    };
+
+    if args.json {
+        if outcome.success {
+            Output::success(&command, result).print();
+        } else {
+            let fix = match &known {
+                Some(known) => known.suggestion(),
+                None => "Run 'rn-run logs analyze' to find the errors in the build output".to_string(),
+            };
+            Output::failure(&command, result, &message, Some(&fix)).print();
+        }
+    } else if outcome.success {
+        println!("\x1b[32m[rn-run]: {}\x1b[0m", message);
+    } else {
+        eprintln!("\x1b[31m[rn-run]: {}\x1b[0m", message);
+        eprintln!("Log: {}", outcome.log_path);
+        if let Some(known) = &known {
+            eprintln!("\x1b[33mKnown issue: {}\x1b[0m", known.suggestion());
         }
     }
+
+    Ok(if outcome.success { 0 } else { outcome.exit_code.filter(|code| *code != 0).unwrap_or(1) })
+}
+
+fn print_simulators_human(result: &IosListResult) {
+    println!("Available iOS Simulators:");
+    for sim in &result.simulators {
+        println!("  {} ({}) - {}", sim.name, sim.runtime, sim.state);
+    }
+    if result.simulators.is_empty() {
+        println!("  No simulators found. Open Xcode > Settings > Platforms to download simulators.");
+        println!("  Tip: Add a simulator named \"iPhone 15\" as it is the default for rn-run.");
+    }
+
+    if !result.devices.is_empty() {
+        println!();
+        println!("Connected iOS Devices:");
+        for device in &result.devices {
+            let details: Vec<&str> = [device.model.as_deref(), device.os_version.as_deref(), device.transport.as_deref()]
+                .into_iter()
+                .flatten()
+                .collect();
+            println!("  {} ({}) {} - {}", device.name, details.join(", "), device.udid, device.state);
+        }
+    }
+}
+
+fn print_emulators_human(result: &EmulatorListResult) {
+    println!("Available Android Emulators:");
+    for avd in &result.avds {
+        let api = avd.api_level.map(|level| format!("API {}", level));
+        let details: Vec<&str> = [api.as_deref(), avd.abi.as_deref(), avd.device.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let running = avd.serial.as_ref().map(|serial| format!(" - running as {}", serial)).unwrap_or_default();
+        println!("  {} ({}){}", avd.name, details.join(", "), running);
+    }
+    if result.avds.is_empty() {
+        println!("  No emulators found. Open Android Studio > Device Manager to create one.");
+    }
+
+    println!();
+    println!("Connected Android Devices:");
+    for device in &result.devices {
+        let model = device.model.as_deref().or(device.avd.as_deref()).unwrap_or("unknown model");
+        println!("  {} {} ({}) - {}", device.serial, model, device.transport, device.state);
+    }
+    if result.devices.is_empty() {
+        println!("  No devices connected. Enable USB debugging on a device, or start an emulator with 'rn-run devices android start'.");
+    }
 }
 
 fn print_env_check_human(result: &output::EnvCheckResult) {
@@ -561,21 +961,63 @@ fn print_env_check_human(result: &output::EnvCheckResult) {
     println!("\n{}", result.summary);
 }
 
-/// Get a suggested fix for common errors (for AI consumption)
-fn get_error_fix(error: &AppError) -> Option<String> {
-    match error {
-        AppError::ReactNativeNotFound => {
-            Some("Make sure you're in a React Native project directory with package.json containing react-native dependency".to_string())
-        },
-        AppError::CommandFailed(cmd) => {
-            if cmd.contains("pod install") {
-                Some("Try: rn-run --clean-pods && rn-run --pod-install".to_string())
-            } else if cmd.contains("node_modules") {
-                Some("Try: rn-run --clean-modules && npm install".to_string())
-            } else {
-                None
+fn print_metro_status_human(result: &MetroStatusResult) {
+    if result.instances.is_empty() {
+        println!("No Metro servers running (this project uses port {})", result.configured_port);
+        return;
+    }
+
+    println!("Metro servers:\n");
+    for instance in &result.instances {
+        let marker = if instance.port == result.configured_port { "*" } else { " " };
+        let root = instance.project_root.as_deref()
+            .or(instance.cwd.as_deref())
+            .unwrap_or("unknown project");
+        println!("  {} :{}  pid {}  {}", marker, instance.port, instance.pid, root);
+        if let (Some(project_root), Some(cwd)) = (&instance.project_root, &instance.cwd) {
+            if project_root != cwd {
+                println!("      cwd: {}", cwd);
+            }
+        }
+    }
+    println!("\n{}", result.message);
+}
+
+fn print_build_failures_human(report: &BuildFailureReport) {
+    if !report.failed && report.known_error.is_none() {
+        println!("\x1b[32m[rn-run]: {}\x1b[0m", report.message);
+        return;
+    }
+
+    for failure in &report.failures {
+        let location = match (&failure.file, failure.line, failure.column) {
+            (Some(file), Some(line), Some(column)) => format!(" {}:{}:{}", file, line, column),
+            (Some(file), Some(line), None) => format!(" {}:{}", file, line),
+            (Some(file), None, _) => format!(" {}", file),
+            _ => String::new(),
+        };
+        println!("\x1b[31m✗ [{}]{}\x1b[0m", failure.category, location);
+        println!("  {}", failure.message);
+        if let Some(task) = &failure.task {
+            if !failure.message.contains(task.as_str()) {
+                println!("  task: {}", task);
             }
-        },
-        _ => None,
+        }
+        for line in &failure.excerpt {
+            println!("    | {}", line);
+        }
+        println!();
+    }
+    println!("{}", report.message);
+    if let Some(known) = &report.known_error {
+        println!("\n\x1b[33mKnown issue ({}): {}\x1b[0m", known.id, known.title);
+        println!("  Fix: {}", known.fix);
+        println!("  Run 'rn-run explain {}' for details.", known.id);
     }
 }
+
+fn print_known_error_human(error: &KnownError) {
+    println!("{} ({})\n", error.title, error.id);
+    println!("{}\n", error.explanation.trim());
+    println!("Fix: {}", error.fix);
+}
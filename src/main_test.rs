@@ -1,5 +1,6 @@
 #[cfg(test)]
 pub mod tests {
+    use crate::runner::SystemRunner;
     use crate::utils::{get_current_directory, kill_process};
 
     #[test]
@@ -18,7 +19,7 @@ pub mod tests {
             .unwrap();
 
         // kill the process
-        kill_process();
+        let _ = kill_process(&SystemRunner, 8081);
 
         // check if the process is killed
         let output = std::process::Command::new("lsof")
@@ -29,4 +30,4 @@ pub mod tests {
 
         assert_eq!(String::from_utf8_lossy(&output.stdout), "");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,383 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::error::{AppError, Result};
+use crate::output::{MetroInstance, MetroReadiness, MetroStatusResult};
+use crate::process::is_metro_command;
+use crate::runner::CommandRunner;
+
+const STATUS_TIMEOUT: Duration = Duration::from_millis(500);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long to wait for a freshly launched Metro before giving up on the build.
+pub const READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Response to `GET /status` from a Metro server.
+pub struct MetroStatus {
+    /// Body of the response, "packager-status:running" for a healthy Metro
+    pub body: String,
+    /// Value of the `X-React-Native-Project-Root` header
+    pub project_root: Option<String>,
+}
+
+impl MetroStatus {
+    pub fn is_running(&self) -> bool {
+        self.body.trim() == "packager-status:running"
+    }
+}
+
+fn connect(port: u16) -> io::Result<TcpStream> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let stream = TcpStream::connect_timeout(&addr, STATUS_TIMEOUT)?;
+    stream.set_read_timeout(Some(STATUS_TIMEOUT))?;
+    stream.set_write_timeout(Some(STATUS_TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Send a bodyless HTTP request to Metro on localhost and return the raw response.
+fn http_request(port: u16, method: &str, path: &str) -> io::Result<String> {
+    let mut stream = connect(port)?;
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost:{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        method, path, port
+    );
+    stream.write_all(request.as_bytes())?;
+
+    let mut response = Vec::new();
+    // A read timeout after the body arrived still leaves a usable response
+    let _ = stream.read_to_end(&mut response);
+    Ok(String::from_utf8_lossy(&response).to_string())
+}
+
+/// Status code from the first line of an HTTP response, e.g. 200 for "HTTP/1.1 200 OK".
+fn status_code(response: &str) -> Option<u16> {
+    response.lines().next()?.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Query Metro's `/status` endpoint on localhost. None if nothing answers on `port`
+/// or the response is not HTTP.
+pub fn fetch_status(port: u16) -> Option<MetroStatus> {
+    let response = http_request(port, "GET", "/status").ok()?;
+    parse_status_response(&response)
+}
+
+fn parse_status_response(response: &str) -> Option<MetroStatus> {
+    if !response.starts_with("HTTP/") {
+        return None;
+    }
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((response, ""));
+    let project_root = head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("x-react-native-project-root")
+            .then(|| value.trim().to_string())
+    });
+
+    Some(MetroStatus { body: body.to_string(), project_root })
+}
+
+/// Fail if Metro for another project is serving `port`, instead of killing it.
+/// A Metro for this project, or one too old to report its project root, is left
+/// for the caller to restart.
+pub fn ensure_port_available(port: u16, project_dir: &Path) -> Result<()> {
+    let project_root = match fetch_status(port) {
+        Some(status) if status.is_running() => status.project_root,
+        _ => return Ok(()),
+    };
+
+    match project_root {
+        Some(root) if !same_path(Path::new(&root), project_dir) => Err(AppError::MetroPortInUse(port, root)),
+        _ => Ok(()),
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Poll `/status` until Metro reports `packager-status:running`, failing with
+/// `MetroNotReady` once `timeout` has passed.
+pub fn wait_until_ready(port: u16, timeout: Duration) -> Result<MetroReadiness> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = fetch_status(port).filter(|status| status.is_running()) {
+            return Ok(MetroReadiness {
+                port,
+                ready: true,
+                waited_ms: started.elapsed().as_millis() as u64,
+                project_root: status.project_root,
+            });
+        }
+
+        let remaining = timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Err(AppError::MetroNotReady(port, timeout));
+        }
+        std::thread::sleep(POLL_INTERVAL.min(remaining));
+    }
+}
+
+/// Every Metro server running on this machine, found by asking each listening
+/// Node process for Metro's `/status`. `configured_port` is the port this
+/// project uses, reported so callers can tell whether its Metro is up.
+pub fn metro_status(runner: &dyn CommandRunner, configured_port: u16) -> MetroStatusResult {
+    let mut listeners = runner.tcp_listeners().unwrap_or_default();
+    // A port listened on over both IPv4 and IPv6 is one server
+    listeners.sort_by_key(|listener| listener.port);
+    listeners.dedup_by_key(|listener| listener.port);
+
+    let instances: Vec<MetroInstance> = listeners
+        .into_iter()
+        .filter_map(|listener| {
+            let info = runner.process_info(listener.pid).filter(|info| is_metro_command(&info.command))?;
+            let status = fetch_status(listener.port).filter(|status| status.is_running())?;
+            Some(MetroInstance {
+                port: listener.port,
+                pid: listener.pid,
+                project_root: status.project_root,
+                cwd: info.cwd,
+            })
+        })
+        .collect();
+
+    let configured_running = instances.iter().any(|instance| instance.port == configured_port);
+    let message = match (instances.len(), configured_running) {
+        (0, _) => "No Metro servers running".to_string(),
+        (count, true) => format!("{} Metro server(s) running, including this project's port {}", count, configured_port),
+        (count, false) => format!("{} Metro server(s) running, none on this project's port {}", count, configured_port),
+    };
+
+    MetroStatusResult {
+        configured_port,
+        configured_running,
+        instances,
+        message,
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// METRO CONTROL
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Message broadcast to connected apps to open the dev menu.
+const DEV_MENU_MESSAGE: &str = r#"{"version":2,"method":"devMenu"}"#;
+
+/// Fail with `MetroNotRunning` unless a Metro server answers on `port`.
+fn require_metro(port: u16) -> Result<()> {
+    match fetch_status(port) {
+        Some(status) if status.is_running() => Ok(()),
+        _ => Err(AppError::MetroNotRunning(port)),
+    }
+}
+
+/// POST to a Metro endpoint and fail unless it answers with a 2xx status.
+fn post(port: u16, path: &str) -> Result<()> {
+    let response = http_request(port, "POST", path).map_err(|_| AppError::MetroNotRunning(port))?;
+    match status_code(&response) {
+        Some(code) if (200..300).contains(&code) => Ok(()),
+        Some(404) if path == "/open-debugger" => {
+            Err(AppError::MetroRequestFailed("no app is connected to Metro to debug".to_string()))
+        }
+        Some(code) => Err(AppError::MetroRequestFailed(format!("POST {} returned HTTP {}", path, code))),
+        None => Err(AppError::MetroRequestFailed(format!("POST {} returned no HTTP response", path))),
+    }
+}
+
+/// Reload the JS bundle in every app connected to Metro on `port`, like pressing `r`.
+pub fn reload(port: u16) -> Result<()> {
+    require_metro(port)?;
+    post(port, "/reload")
+}
+
+/// Open the debugger for the app connected to Metro on `port`, like pressing `j`.
+pub fn open_debugger(port: u16) -> Result<()> {
+    require_metro(port)?;
+    post(port, "/open-debugger")
+}
+
+/// Show the dev menu in every app connected to Metro on `port`, like pressing `d`.
+/// Metro has no HTTP endpoint for this, so the command is broadcast over its
+/// `/message` WebSocket.
+pub fn open_dev_menu(port: u16) -> Result<()> {
+    require_metro(port)?;
+    broadcast(port, DEV_MENU_MESSAGE)
+        .map_err(|e| AppError::MetroRequestFailed(format!("broadcast over /message failed: {}", e)))
+}
+
+/// Connect to Metro's `/message` WebSocket, send `message` as one text frame and close.
+fn broadcast(port: u16, message: &str) -> io::Result<()> {
+    let mut stream = connect(port)?;
+    let handshake = format!(
+        "GET /message HTTP/1.1\r\nHost: localhost:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: cm4tcnVuLW1ldHJvLWtleQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        port
+    );
+    stream.write_all(handshake.as_bytes())?;
+
+    // Read the handshake response headers, up to the blank line
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            break;
+        }
+        response.push(byte[0]);
+    }
+    let response = String::from_utf8_lossy(&response);
+    if status_code(&response) != Some(101) {
+        let status = response.lines().next().unwrap_or("no response").to_string();
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("WebSocket upgrade refused: {}", status)));
+    }
+
+    stream.write_all(&websocket_frame(0x1, message.as_bytes()))?;
+    // The message is out; a close frame Metro never reads doesn't matter
+    let _ = stream.write_all(&websocket_frame(0x8, &[]));
+    Ok(())
+}
+
+/// A single final, masked client frame (RFC 6455 section 5.2).
+fn websocket_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    // Masking is mandatory for clients but protects nothing here, so the key is fixed
+    const MASK: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&MASK);
+    frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ MASK[i % 4]));
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    /// Stub Metro server handling `connections` requests the way Metro does:
+    /// `/status`, POSTs to control endpoints, and the `/message` WebSocket.
+    /// Returns the request lines it saw, plus the text of any WebSocket message.
+    fn serve_metro(project_root: &str, connections: usize) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let status = format!(
+            "HTTP/1.1 200 OK\r\nX-React-Native-Project-Root: {}\r\nContent-Length: 23\r\n\r\npackager-status:running",
+            project_root
+        );
+        let server = std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for _ in 0..connections {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = Vec::new();
+                let mut byte = [0u8; 1];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+                    head.push(byte[0]);
+                }
+                let head = String::from_utf8_lossy(&head).to_string();
+                let request_line = head.lines().next().unwrap_or("").to_string();
+                seen.push(request_line.clone());
+
+                if request_line.starts_with("GET /status") {
+                    stream.write_all(status.as_bytes()).unwrap();
+                } else if head.contains("Upgrade: websocket") {
+                    stream.write_all(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n").unwrap();
+                    let mut header = [0u8; 6];
+                    stream.read_exact(&mut header).unwrap();
+                    let mut payload = vec![0u8; (header[1] & 0x7f) as usize];
+                    stream.read_exact(&mut payload).unwrap();
+                    let text: Vec<u8> = payload.iter().enumerate().map(|(i, b)| b ^ header[2 + i % 4]).collect();
+                    seen.push(String::from_utf8(text).unwrap());
+                } else {
+                    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK").unwrap();
+                }
+            }
+            seen
+        });
+        (port, server)
+    }
+
+    fn closed_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    #[test]
+    fn test_metro_status_finds_instances_by_port() {
+        let (port, server) = serve_metro("/work/app-one", 1);
+        let runner = FakeRunner::new()
+            .on("listeners", &format!("5432 777\n{0} 4242\n{0} 4242\n", port))
+            .on("process 777", "postgres -D /usr/local/var/postgres\n")
+            .on("process 4242", "node /work/app-one/node_modules/.bin/react-native start\n/work/app-one\n");
+
+        let result = metro_status(&runner, port);
+        server.join().unwrap();
+
+        assert_eq!(result.instances.len(), 1);
+        assert_eq!(result.instances[0].port, port);
+        assert_eq!(result.instances[0].pid, 4242);
+        assert_eq!(result.instances[0].project_root.as_deref(), Some("/work/app-one"));
+        assert_eq!(result.instances[0].cwd.as_deref(), Some("/work/app-one"));
+        assert!(result.configured_running);
+    }
+
+    #[test]
+    fn test_own_metro_may_be_restarted_and_reports_ready() {
+        let project = std::env::current_dir().unwrap();
+        let (port, server) = serve_metro(&project.to_string_lossy(), 2);
+
+        ensure_port_available(port, &project).unwrap();
+        let readiness = wait_until_ready(port, Duration::from_secs(5)).unwrap();
+        assert!(readiness.ready);
+        assert_eq!(readiness.project_root, Some(project.to_string_lossy().to_string()));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_other_projects_metro_is_not_ours_to_kill() {
+        let project = std::env::current_dir().unwrap();
+        let (port, server) = serve_metro("/work/other-app", 1);
+        match ensure_port_available(port, &project) {
+            Err(AppError::MetroPortInUse(busy, root)) => {
+                assert_eq!(busy, port);
+                assert_eq!(root, "/work/other-app");
+            }
+            other => panic!("expected MetroPortInUse, got {:?}", other),
+        }
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_until_ready_times_out() {
+        assert!(matches!(
+            wait_until_ready(closed_port(), Duration::from_millis(200)),
+            Err(AppError::MetroNotReady(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_metro_control_commands() {
+        let (port, server) = serve_metro("/work/app-one", 4);
+        reload(port).unwrap();
+        open_dev_menu(port).unwrap();
+
+        assert_eq!(server.join().unwrap(), [
+            "GET /status HTTP/1.1",
+            "POST /reload HTTP/1.1",
+            "GET /status HTTP/1.1",
+            "GET /message HTTP/1.1",
+            r#"{"version":2,"method":"devMenu"}"#,
+        ]);
+        assert!(matches!(open_debugger(closed_port()), Err(AppError::MetroNotRunning(_))));
+    }
+}
@@ -1,4 +1,7 @@
 use serde::Serialize;
+use crate::config::Config;
+use crate::known_errors::KnownError;
+use crate::utils::{LogMetadata, LogStatus};
 
 /// Wrapper for all JSON output from rn-run commands.
 /// AI/automation tools should parse this structure.
@@ -30,6 +33,16 @@ impl<T: Serialize> Output<T> {
         }
     }
 
+    pub fn failure(command: &str, data: T, error: &str, suggested_fix: Option<&str>) -> Self {
+        Output {
+            command: command.to_string(),
+            success: false,
+            data: Some(data),
+            error: Some(error.to_string()),
+            suggested_fix: suggested_fix.map(|s| s.to_string()),
+        }
+    }
+
     pub fn error(command: &str, error: &str, suggested_fix: Option<&str>) -> Output<()> {
         Output {
             command: command.to_string(),
@@ -76,10 +89,24 @@ pub struct EnvCheck {
 #[derive(Serialize)]
 pub struct RnVersionResult {
     pub version: String,
-    pub package_manager: String, // "npm" or "yarn"
+    pub specifier: String, // raw value from package.json, e.g. "^0.74.3"
+    pub source: String,    // "node_modules" or "package.json"
+    pub package_manager: String, // "npm", "yarn", "pnpm" or "bun"
     pub notes: Vec<String>,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIG DATA STRUCTURES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+pub struct ConfigResult {
+    /// Config files that were loaded, lowest precedence first
+    pub sources: Vec<String>,
+    /// Effective config after merging files and CLI flags
+    pub config: Config,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SIMULATOR/EMULATOR LIST DATA STRUCTURES
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -89,7 +116,7 @@ pub struct SimulatorListResult {
     pub simulators: Vec<Simulator>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 pub struct Simulator {
     pub name: String,
     pub udid: String,
@@ -97,19 +124,232 @@ pub struct Simulator {
     pub runtime: String,
 }
 
+/// `devices list ios`: simulators and physical devices.
+#[derive(Serialize)]
+pub struct IosListResult {
+    pub simulators: Vec<Simulator>,
+    pub devices: Vec<IosDevice>,
+}
+
+/// A physical iOS device paired with this Mac.
+#[derive(Serialize, Clone)]
+pub struct IosDevice {
+    pub name: String,
+    pub udid: String,
+    /// Marketing name, e.g. "iPhone 14 Pro" (devicectl only)
+    pub model: Option<String>,
+    pub os_version: Option<String>,
+    /// "usb" or "wifi" (devicectl only)
+    pub transport: Option<String>,
+    /// "connected" or "offline"
+    pub state: String,
+}
+
 #[derive(Serialize)]
 pub struct EmulatorListResult {
+    /// AVD names, kept for scripts written against earlier releases
+    pub emulators: Vec<String>,
+    pub avds: Vec<Avd>,
+    /// Everything connected to adb, physical devices and running emulators
+    pub devices: Vec<AndroidDevice>,
+}
+
+/// An Android Virtual Device. Fields missing from its config files are null.
+#[derive(Serialize)]
+pub struct Avd {
+    pub name: String,
+    pub display_name: Option<String>,
+    pub api_level: Option<u32>,
+    pub abi: Option<String>,
+    /// Hardware profile, e.g. "pixel_7"
+    pub device: Option<String>,
+    /// System image variant, e.g. "google_apis" or "google_apis_playstore"
+    pub image: Option<String>,
+    /// adb serial while the emulator is running
+    pub serial: Option<String>,
+}
+
+/// A device from `adb devices -l`.
+#[derive(Serialize)]
+pub struct AndroidDevice {
+    pub serial: String,
+    /// "device", "unauthorized", "offline", "no permissions", ...
+    pub state: String,
+    pub model: Option<String>,
+    pub product: Option<String>,
+    pub device: Option<String>,
+    /// "usb", "wifi", "emulator" or "unknown"
+    pub transport: String,
+    pub transport_id: Option<String>,
+    pub is_emulator: bool,
+    /// AVD name, for running emulators
+    pub avd: Option<String>,
+}
+
+/// `devices list` without a platform: simulators and emulators together.
+#[derive(Serialize)]
+pub struct DeviceListResult {
+    pub simulators: Vec<Simulator>,
+    pub ios_devices: Vec<IosDevice>,
     pub emulators: Vec<String>,
+    pub avds: Vec<Avd>,
+    pub android_devices: Vec<AndroidDevice>,
+}
+
+/// `devices ios boot|shutdown|erase|create`.
+#[derive(Serialize)]
+pub struct SimulatorActionResult {
+    pub action: String,
+    pub simulator: Simulator,
+    /// False when the simulator was already in the requested state
+    pub changed: bool,
+    pub message: String,
+}
+
+/// `devices android start|stop`.
+#[derive(Serialize)]
+pub struct EmulatorActionResult {
+    pub action: String,
+    pub avd: String,
+    /// adb serial, e.g. "emulator-5554"
+    pub serial: String,
+    /// False when the emulator was already running
+    pub changed: bool,
+    /// Time spent waiting for Android to finish booting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waited_ms: Option<u64>,
+    pub message: String,
+}
+
+/// `devices android reverse`: devices whose Metro port now reaches this machine.
+#[derive(Serialize)]
+pub struct ReverseResult {
+    pub port: u16,
+    pub devices: Vec<String>,
+    pub message: String,
+}
+
+/// `devices ios provision`: the configured simulator set.
+#[derive(Serialize)]
+pub struct ProvisionResult {
+    pub created: Vec<Simulator>,
+    pub existing: Vec<Simulator>,
+    pub message: String,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// SIMPLE ACTION RESULTS
+// CLEANUP DATA STRUCTURES
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[derive(Serialize)]
+pub struct CleanupResult {
+    pub success: bool,
+    pub steps: Vec<CleanupStepResult>,
+    pub bytes_freed: u64,
+    pub duration_ms: u64,
+    pub message: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct CleanupStepResult {
+    pub action: String, // "clean-pods", "pod-install", ...
+    pub status: String, // "ok", "failed", "skipped"
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<RemovedPath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_freed: Option<u64>, // absent for install steps
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DRY-RUN DATA STRUCTURES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+pub struct DryRunResult {
+    /// The command that was dry-run, e.g. "clean pods gradle"
+    pub command: String,
+    pub deletions: Vec<PlannedDeletion>,
+    pub total_bytes: u64,
+    pub processes: Vec<PlannedKill>,
+    /// Every external command that would be executed, in order
+    pub commands: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PlannedDeletion {
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct PlannedKill {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Command line of the process, or "emulator <serial>" for emulators
+    pub process: String,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SIMPLE ACTION RESULTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize, Default)]
 pub struct ActionResult {
     pub action: String,
     pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<RemovedPath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_freed: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<KilledProcess>,
+}
+
+/// A process stopped by rn-run.
+#[derive(Serialize, Clone)]
+pub struct KilledProcess {
+    pub pid: u32,
+    pub command: String,
+    pub signal: String, // "SIGTERM", or "SIGKILL" if it ignored SIGTERM
+}
+
+/// A file or directory deleted by rn-run, with the space it took up.
+#[derive(Serialize, Clone)]
+pub struct RemovedPath {
+    pub path: String,
+    pub bytes: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// BUILD RESULT (foreground runs)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+pub struct BuildResult {
+    pub platform: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub log_path: String,
+    pub message: String,
+}
+
+/// A build handed off to a new terminal window.
+#[derive(Serialize)]
+pub struct LaunchResult {
+    pub platform: String,
+    /// Present when rn-run started Metro itself and waited for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metro: Option<MetroReadiness>,
+    pub message: String,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -147,14 +387,128 @@ pub struct LogListResult {
 
 #[derive(Serialize, Clone)]
 pub struct LogEntryOutput {
+    /// Number to pass to `logs show`
+    pub index: usize,
     pub path: String,
     pub name: String,
     pub size: u64,
     pub modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    pub status: LogStatus,
+    /// Absent for logs written before rn-run kept metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<LogMetadata>,
 }
 
 #[derive(Serialize)]
 pub struct LogContentResult {
     pub path: String,
+    pub name: String,
+    pub status: LogStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<LogMetadata>,
+    /// Whether `content` is the log as written, or cleaned up
+    pub raw: bool,
+    /// With --head/--tail, only those lines
     pub content: String,
+    pub analysis: BuildFailureReport,
+}
+
+#[derive(Serialize)]
+pub struct LogAnalysisResult {
+    pub path: String,
+    #[serde(flatten)]
+    pub analysis: BuildFailureReport,
+}
+
+#[derive(Serialize)]
+pub struct LogFollowResult {
+    pub path: String,
+    /// Whether the log ends with the build's result marker
+    pub done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Lines that passed the filters
+    pub lines: Vec<String>,
+}
+
+/// Errors found in a build log.
+#[derive(Serialize, Debug, Clone)]
+pub struct BuildFailureReport {
+    pub failed: bool,
+    pub failures: Vec<BuildFailure>,
+    /// Catalogued failure the log matches, see `rn-run explain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_error: Option<KnownError>,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BuildFailure {
+    /// xcode, gradle, kotlin, java, cocoapods or npm
+    pub category: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    /// Gradle task or Xcode build command that failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Log lines around the error
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub excerpt: Vec<String>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// KNOWN ERRORS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+pub struct KnownErrorListResult {
+    /// User catalogue, whether or not it exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_file: Option<String>,
+    pub errors: Vec<KnownError>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// METRO STATUS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+pub struct MetroStatusResult {
+    /// Port configured for this project (--port, [metro] port, or 8081)
+    pub configured_port: u16,
+    pub configured_running: bool,
+    pub instances: Vec<MetroInstance>,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct MetroReadiness {
+    pub port: u16,
+    pub ready: bool,
+    pub waited_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MetroInstance {
+    pub port: u16,
+    pub pid: u32,
+    /// Project root reported by Metro's /status endpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<String>,
+    /// Working directory of the Metro process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
 }
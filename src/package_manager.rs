@@ -0,0 +1,203 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use crate::config::Config;
+use crate::version::{resolve_react_native_version, Version};
+
+/// JavaScript package manager used for install, run and start commands.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+    Bun,
+}
+
+impl PackageManager {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "npm" => Some(PackageManager::Npm),
+            "yarn" => Some(PackageManager::Yarn),
+            "pnpm" => Some(PackageManager::Pnpm),
+            "bun" => Some(PackageManager::Bun),
+            _ => None,
+        }
+    }
+
+    /// Lockfile written by this package manager.
+    pub fn lockfile(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "package-lock.json",
+            PackageManager::Yarn => "yarn.lock",
+            PackageManager::Pnpm => "pnpm-lock.yaml",
+            PackageManager::Bun => "bun.lockb",
+        }
+    }
+
+    /// Command that installs the project's dependencies.
+    pub fn install(&self) -> String {
+        format!("{} install", self.name())
+    }
+
+    /// Prefix for running a binary from node_modules, e.g. `npx react-native`.
+    pub fn exec(&self, bin: &str) -> String {
+        match self {
+            PackageManager::Npm => format!("npx {}", bin),
+            PackageManager::Yarn => format!("yarn {}", bin),
+            PackageManager::Pnpm => format!("pnpm exec {}", bin),
+            PackageManager::Bun => format!("bunx {}", bin),
+        }
+    }
+
+    /// Command for running a package.json script.
+    pub fn run_script(&self, script: &str) -> String {
+        match self {
+            PackageManager::Yarn => format!("yarn {}", script),
+            _ => format!("{} run {}", self.name(), script),
+        }
+    }
+
+    /// Command for running a package.json script with extra arguments.
+    /// npm needs `--` to pass them through to the script.
+    pub fn run_script_with_args(&self, script: &str, args: &str) -> String {
+        match self {
+            PackageManager::Npm => format!("npm run {} -- {}", script, args),
+            _ => format!("{} {}", self.run_script(script), args),
+        }
+    }
+}
+
+/// Package manager together with where the choice came from.
+pub struct PackageManagerDetection {
+    pub manager: PackageManager,
+    pub source: String,
+}
+
+#[derive(Deserialize)]
+struct PackageJsonManager {
+    #[serde(rename = "packageManager")]
+    package_manager: Option<String>,
+}
+
+/// Resolve the package manager for a project. In order of precedence:
+/// `--package-manager`, the rn-run config, the Corepack `packageManager` field
+/// in package.json, lockfiles, and finally the RN version (npm for 0.74+, yarn before).
+pub fn resolve_package_manager(cli: Option<PackageManager>, config: &Config, project_dir: &Path, react_native_version: &Version) -> PackageManagerDetection {
+    if let Some(manager) = cli {
+        return PackageManagerDetection { manager, source: "--package-manager".to_string() };
+    }
+
+    if let Some(manager) = config.package_manager {
+        return PackageManagerDetection { manager, source: "rn-run config".to_string() };
+    }
+
+    detect_package_manager(project_dir, react_native_version)
+}
+
+/// The package manager `run` would use in the current directory, leaving out
+/// `--package-manager`. None outside a React Native project. Detected once per
+/// run, as error suggestions ask for it each time.
+pub fn current_package_manager() -> Option<PackageManager> {
+    static CURRENT: OnceLock<Option<PackageManager>> = OnceLock::new();
+    *CURRENT.get_or_init(|| {
+        let project_dir = env::current_dir().ok()?;
+        let (config, _) = Config::load(&project_dir).ok()?;
+        let react_native = resolve_react_native_version(&project_dir).ok()?;
+        Some(resolve_package_manager(None, &config, &project_dir, &react_native.version).manager)
+    })
+}
+
+pub fn detect_package_manager(project_dir: &Path, react_native_version: &Version) -> PackageManagerDetection {
+    // Corepack: "packageManager": "yarn@4.1.0"
+    let corepack = fs::read_to_string(project_dir.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<PackageJsonManager>(&contents).ok())
+        .and_then(|package_json| package_json.package_manager);
+
+    if let Some(spec) = corepack {
+        let name = spec.split('@').next().unwrap_or("");
+        if let Some(manager) = PackageManager::from_name(name) {
+            return PackageManagerDetection { manager, source: format!("packageManager field ({})", spec) };
+        }
+    }
+
+    // Lockfiles, most specific first (bun and pnpm projects may carry a stale package-lock.json)
+    let lockfiles = [
+        ("bun.lockb", PackageManager::Bun),
+        ("bun.lock", PackageManager::Bun),
+        ("pnpm-lock.yaml", PackageManager::Pnpm),
+        ("yarn.lock", PackageManager::Yarn),
+        ("package-lock.json", PackageManager::Npm),
+    ];
+    for (lockfile, manager) in lockfiles {
+        if project_dir.join(lockfile).exists() {
+            return PackageManagerDetection { manager, source: lockfile.to_string() };
+        }
+    }
+
+    if react_native_version.at_least(0, 74) {
+        PackageManagerDetection { manager: PackageManager::Npm, source: "default for RN 0.74+".to_string() }
+    } else {
+        PackageManagerDetection { manager: PackageManager::Yarn, source: "default for RN < 0.74".to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn rn_076() -> Version {
+        Version::parse("0.76.1").unwrap()
+    }
+
+    fn project() -> TempDir {
+        let dir = TempDir::new("package-manager");
+        dir.write("package.json", r#"{"dependencies": {"react-native": "0.76.1"}}"#);
+        dir
+    }
+
+    #[test]
+    fn test_detect_falls_back_to_react_native_version() {
+        let dir = project();
+        assert_eq!(detect_package_manager(dir.path(), &rn_076()).manager, PackageManager::Npm);
+        assert_eq!(detect_package_manager(dir.path(), &Version::parse("0.72.4").unwrap()).manager, PackageManager::Yarn);
+    }
+
+    #[test]
+    fn test_detect_prefers_most_specific_lockfile() {
+        let dir = project();
+        dir.write("package-lock.json", "{}");
+        dir.write("pnpm-lock.yaml", "");
+        assert_eq!(detect_package_manager(dir.path(), &rn_076()).manager, PackageManager::Pnpm);
+    }
+
+    #[test]
+    fn test_detect_prefers_corepack_field_over_lockfiles() {
+        let dir = project();
+        dir.write("pnpm-lock.yaml", "");
+        dir.write("package.json", r#"{"packageManager": "yarn@4.1.0", "dependencies": {"react-native": "0.76.1"}}"#);
+        let detection = detect_package_manager(dir.path(), &rn_076());
+        assert_eq!(detection.manager, PackageManager::Yarn);
+        assert_eq!(detection.manager.exec("react-native"), "yarn react-native");
+    }
+
+    #[test]
+    fn test_run_script_with_args() {
+        assert_eq!(PackageManager::Npm.run_script_with_args("start", "--port 8082"), "npm run start -- --port 8082");
+        assert_eq!(PackageManager::Yarn.run_script_with_args("start", "--port 8082"), "yarn start --port 8082");
+    }
+}
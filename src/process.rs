@@ -0,0 +1,333 @@
+use std::fs;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::runner::CommandRunner;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a process gets to exit after SIGKILL before we give up on it.
+const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A process listening on a TCP port.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    pub port: u16,
+    pub pid: u32,
+}
+
+/// What we know about a running process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+    pub command: String,
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Term,
+    Kill,
+}
+
+impl Signal {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Term => "SIGTERM",
+            Signal::Kill => "SIGKILL",
+        }
+    }
+
+    fn number(&self) -> libc::c_int {
+        match self {
+            Signal::Term => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        }
+    }
+}
+
+/// Every process listening on a TCP port. Reads `/proc/net/tcp{,6}` on Linux,
+/// so no lsof is needed there; other systems fall back to `lsof` through `runner`.
+pub fn tcp_listeners(runner: &dyn CommandRunner) -> io::Result<Vec<Listener>> {
+    if cfg!(target_os = "linux") {
+        proc_tcp_listeners()
+    } else {
+        lsof_tcp_listeners(runner)
+    }
+}
+
+fn proc_tcp_listeners() -> io::Result<Vec<Listener>> {
+    let mut sockets = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        // tcp6 is missing when IPv6 is disabled
+        if let Ok(contents) = fs::read_to_string(table) {
+            sockets.extend(parse_proc_net_tcp(&contents));
+        }
+    }
+    if sockets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Map socket inodes back to their owners through /proc/<pid>/fd/* -> "socket:[inode]"
+    let mut listeners = Vec::new();
+    for entry in fs::read_dir("/proc")?.flatten() {
+        let pid = match entry.file_name().to_string_lossy().parse::<u32>() {
+            Ok(pid) => pid,
+            Err(_) => continue,
+        };
+        // Other users' processes are unreadable, and processes may exit mid-scan
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        for fd in fds.flatten() {
+            let target = match fs::read_link(fd.path()) {
+                Ok(target) => target.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            let inode = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok());
+            if let Some(inode) = inode {
+                for (port, _) in sockets.iter().filter(|(_, socket)| *socket == inode) {
+                    let listener = Listener { port: *port, pid };
+                    if !listeners.contains(&listener) {
+                        listeners.push(listener);
+                    }
+                }
+            }
+        }
+    }
+
+    listeners.sort_by_key(|listener| (listener.port, listener.pid));
+    Ok(listeners)
+}
+
+/// Listening sockets in a `/proc/net/tcp` or `/proc/net/tcp6` table, as (port, inode).
+///
+/// ```text
+///   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+///    0: 00000000:1F91 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 123456 ...
+/// ```
+pub fn parse_proc_net_tcp(contents: &str) -> Vec<(u16, u64)> {
+    const LISTEN: &str = "0A";
+
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != LISTEN {
+                return None;
+            }
+            let port = u16::from_str_radix(fields[1].rsplit(':').next()?, 16).ok()?;
+            let inode = fields[9].parse::<u64>().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+fn lsof_tcp_listeners(runner: &dyn CommandRunner) -> io::Result<Vec<Listener>> {
+    // lsof exits 1 when nothing is listening, so only a missing lsof is an error
+    let output = runner.output("lsof", &["-nP", "-iTCP", "-sTCP:LISTEN"])?;
+    Ok(parse_lsof_listeners(&output.stdout_str()))
+}
+
+/// Listeners in `lsof -nP -iTCP -sTCP:LISTEN` output, e.g.
+/// `node 4242 me 23u IPv6 0x1234 0t0 TCP *:8081 (LISTEN)`.
+/// A port listened on over both IPv4 and IPv6 appears once per process.
+pub fn parse_lsof_listeners(output: &str) -> Vec<Listener> {
+    let mut listeners = Vec::new();
+    for line in output.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 9 {
+            continue;
+        }
+        let pid = fields[1].parse::<u32>().ok();
+        let port = fields[fields.len() - 2]
+            .rsplit(':')
+            .next()
+            .and_then(|port| port.parse::<u16>().ok());
+        if let (Some(pid), Some(port)) = (pid, port) {
+            let listener = Listener { port, pid };
+            if !listeners.contains(&listener) {
+                listeners.push(listener);
+            }
+        }
+    }
+    listeners
+}
+
+/// Command line and working directory of `pid`, or None if it is not running.
+/// Reads `/proc` on Linux; other systems ask `ps` and `lsof` through `runner`.
+pub fn process_info(runner: &dyn CommandRunner, pid: u32) -> Option<ProcessInfo> {
+    if cfg!(target_os = "linux") {
+        proc_process_info(pid)
+    } else {
+        ps_process_info(runner, pid)
+    }
+}
+
+fn proc_process_info(pid: u32) -> Option<ProcessInfo> {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let command = String::from_utf8_lossy(&cmdline).replace('\0', " ").trim().to_string();
+    // Zombies keep their /proc entry but have no command line
+    if command.is_empty() {
+        return None;
+    }
+    let cwd = fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|cwd| cwd.to_string_lossy().to_string());
+    Some(ProcessInfo { command, cwd })
+}
+
+fn ps_process_info(runner: &dyn CommandRunner, pid: u32) -> Option<ProcessInfo> {
+    let pid = pid.to_string();
+    let output = runner.output("ps", &["-p", &pid, "-o", "command="]).ok()?;
+    let command = output.stdout_str().trim().to_string();
+    if !output.success || command.is_empty() {
+        return None;
+    }
+    let cwd = runner
+        .output("lsof", &["-a", "-p", &pid, "-d", "cwd", "-Fn"])
+        .ok()
+        .and_then(|output| {
+            output.stdout_str().lines().find_map(|line| line.strip_prefix('n').map(|path| path.to_string()))
+        });
+    Some(ProcessInfo { command, cwd })
+}
+
+/// Send `signal` to `pid`. A process that has already exited is not an error.
+pub fn send_signal(pid: u32, signal: Signal) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(pid)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid pid {}", pid)))?;
+
+    // SAFETY: kill(2) takes no pointers; an invalid pid or signal only yields an error code
+    if unsafe { libc::kill(pid, signal.number()) } == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ESRCH) => Ok(()),
+        _ => Err(error),
+    }
+}
+
+/// Whether a command line is Node (or Bun) running Metro, e.g.
+/// `node /app/node_modules/.bin/react-native start`. Anything else listening
+/// on the Metro port is not ours to kill, even if its path mentions React Native.
+pub fn is_metro_command(command: &str) -> bool {
+    let basename = |word: &str| word.rsplit('/').next().unwrap_or(word).to_string();
+    let mut words = command.split_whitespace();
+    let program = words.next().map(basename).unwrap_or_default();
+    if !matches!(program.as_str(), "node" | "nodejs" | "bun") {
+        return false;
+    }
+
+    // The script: the react-native, expo or metro CLI, or react-native/cli.js
+    words.any(|arg| {
+        matches!(basename(arg).as_str(), "react-native" | "expo" | "metro") || arg.ends_with("react-native/cli.js")
+    })
+}
+
+/// Ask `pid` to exit with SIGTERM, escalating to SIGKILL if it is still running
+/// after `grace`. Returns the signal that ended it.
+pub fn terminate(runner: &dyn CommandRunner, pid: u32, grace: Duration) -> io::Result<Signal> {
+    runner.send_signal(pid, Signal::Term)?;
+    if wait_for_exit(runner, pid, grace) {
+        return Ok(Signal::Term);
+    }
+
+    runner.send_signal(pid, Signal::Kill)?;
+    if wait_for_exit(runner, pid, KILL_TIMEOUT) {
+        return Ok(Signal::Kill);
+    }
+    Err(io::Error::new(io::ErrorKind::TimedOut, format!("process {} survived SIGKILL", pid)))
+}
+
+fn wait_for_exit(runner: &dyn CommandRunner, pid: u32, timeout: Duration) -> bool {
+    let started = Instant::now();
+    loop {
+        if runner.process_info(pid).is_none() {
+            return true;
+        }
+        let remaining = timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL.min(remaining));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    #[test]
+    fn test_parse_proc_net_tcp_keeps_listeners() {
+        let proc_net_tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+            0: 00000000:1F91 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 123456 1 0000000000000000 100 0 0 10 0\n\
+            1: 0100007F:1F91 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 123999 1 0000000000000000 20 4 30 10 -1\n\
+            2: 0100007F:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000   112        0 654321 1 0000000000000000 100 0 0 10 0\n";
+        // Established connections to the port (state 01) are not listeners
+        assert_eq!(parse_proc_net_tcp(proc_net_tcp), [(8081, 123456), (5432, 654321)]);
+    }
+
+    #[test]
+    fn test_parse_lsof_listeners_once_per_process() {
+        let lsof = "COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME\n\
+                    node     4242 me     23u  IPv6 0x1234      0t0  TCP *:8081 (LISTEN)\n\
+                    node     4242 me     24u  IPv4 0x5678      0t0  TCP 127.0.0.1:8081 (LISTEN)\n\
+                    postgres  777 me      7u  IPv4 0x9abc      0t0  TCP 127.0.0.1:5432 (LISTEN)\n";
+        assert_eq!(parse_lsof_listeners(lsof), [
+            Listener { port: 8081, pid: 4242 },
+            Listener { port: 5432, pid: 777 },
+        ]);
+    }
+
+    #[test]
+    fn test_lsof_and_ps_go_through_the_runner() {
+        let runner = FakeRunner::new()
+            .on("lsof -nP -iTCP -sTCP:LISTEN", "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\n\
+                 node 4242 me 23u IPv6 0x1234 0t0 TCP *:8081 (LISTEN)\n")
+            .on("ps -p 4242 -o command=", "node /app/node_modules/.bin/react-native start\n")
+            .on("lsof -a -p 4242 -d cwd -Fn", "p4242\nfcwd\nn/app\n")
+            .on_fail("ps -p 4243 -o command=", "");
+
+        assert_eq!(lsof_tcp_listeners(&runner).unwrap(), [Listener { port: 8081, pid: 4242 }]);
+        assert_eq!(ps_process_info(&runner, 4242), Some(ProcessInfo {
+            command: "node /app/node_modules/.bin/react-native start".to_string(),
+            cwd: Some("/app".to_string()),
+        }));
+        assert_eq!(ps_process_info(&runner, 4243), None);
+    }
+
+    #[test]
+    fn test_metro_command_is_node_running_metro() {
+        assert!(is_metro_command("node /app/node_modules/.bin/react-native start"));
+        assert!(is_metro_command("/usr/local/bin/node /app/node_modules/react-native/cli.js start --port 8082"));
+        assert!(is_metro_command("bun /app/node_modules/.bin/expo start"));
+        assert!(is_metro_command("nodejs node_modules/.bin/metro serve"));
+    }
+
+    #[test]
+    fn test_metro_command_rejects_other_programs() {
+        // Paths and names that merely mention React Native or Metro
+        assert!(!is_metro_command("/usr/bin/python3 /work/react-native-app/tools/server.py"));
+        assert!(!is_metro_command("/work/react-native-app/bin/api-server --port 8081"));
+        assert!(!is_metro_command("/usr/local/bin/metronome --listen 8081"));
+        assert!(!is_metro_command("node /work/react-native-app/server.js"));
+        assert!(!is_metro_command("nodemon server.js"));
+    }
+
+    #[test]
+    fn test_terminate_escalates_to_sigkill() {
+        // A Metro that ignores SIGTERM gets SIGKILL after the grace period
+        let runner = FakeRunner::new()
+            .on("process 4242", "node metro\n")
+            .on_fail("kill -TERM 4242", "")
+            .on("kill -KILL 4242", "");
+        let signal = terminate(&runner, 4242, Duration::from_millis(50)).unwrap();
+        assert_eq!(signal, Signal::Kill);
+    }
+}
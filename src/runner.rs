@@ -0,0 +1,267 @@
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use crate::error::Result;
+use crate::process::{Listener, ProcessInfo, Signal};
+
+/// Executes external tools (xcrun, adb, lsof, watchman, ...).
+/// All run, diagnostic and cleanup paths go through this trait so they can be
+/// exercised in tests with `FakeRunner` instead of real tools.
+pub trait CommandRunner {
+    /// Run a command to completion and capture its output.
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput>;
+
+    /// Run a command with inherited stdio and return whether it exited successfully.
+    fn status(&self, program: &str, args: &[&str]) -> io::Result<bool>;
+
+    /// Start a command in the background without waiting for it. Its output is
+    /// discarded so it can't interleave with ours.
+    fn spawn(&self, program: &str, args: &[&str], cwd: Option<&str>) -> io::Result<()>;
+
+    /// Run a command in `cwd` to completion, handing its stdout and stderr to
+    /// `tee` while it runs (the flag is true for stderr). Returns the exit code,
+    /// or None when the command was killed by a signal.
+    fn stream(&self, program: &str, args: &[&str], cwd: Option<&str>, tee: &StreamSink<'_>) -> io::Result<Option<i32>>;
+
+    /// Delete a file or directory tree natively and return the bytes freed,
+    /// or None when nothing was there. Routed through the runner so dry runs
+    /// and tests can intercept it.
+    fn remove_path(&self, path: &Path) -> Result<Option<u64>> {
+        crate::utils::remove_path(path)
+    }
+
+    /// Every process listening on a TCP port.
+    fn tcp_listeners(&self) -> io::Result<Vec<Listener>>;
+
+    /// Command line and working directory of `pid`, or None if it is not running.
+    fn process_info(&self, pid: u32) -> Option<ProcessInfo>;
+
+    /// Send a signal to `pid` without spawning `kill`.
+    fn send_signal(&self, pid: u32, signal: Signal) -> io::Result<()> {
+        crate::process::send_signal(pid, signal)
+    }
+
+    /// Whether commands are only being recorded (`--dry-run`). Code with side effects
+    /// outside the runner, such as writing build logs, checks this.
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Receives a streamed command's output: the pipe, and whether it is stderr.
+/// Called from one thread per pipe.
+pub type StreamSink<'a> = dyn Fn(&mut dyn Read, bool) + Sync + 'a;
+
+/// Captured result of a finished command.
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    pub fn stdout_str(&self) -> String {
+        String::from_utf8_lossy(&self.stdout).to_string()
+    }
+
+    pub fn stderr_str(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).to_string()
+    }
+}
+
+/// Runs commands on the host with `std::process::Command`.
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        let output = Command::new(program).args(args).output()?;
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    fn status(&self, program: &str, args: &[&str]) -> io::Result<bool> {
+        Ok(Command::new(program).args(args).status()?.success())
+    }
+
+    fn spawn(&self, program: &str, args: &[&str], cwd: Option<&str>) -> io::Result<()> {
+        let mut command = Command::new(program);
+        command.args(args).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
+        command.spawn().map(|_| ())
+    }
+
+    fn stream(&self, program: &str, args: &[&str], cwd: Option<&str>, tee: &StreamSink<'_>) -> io::Result<Option<i32>> {
+        let mut command = Command::new(program);
+        command.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+        if let Some(cwd) = cwd {
+            command.current_dir(cwd);
+        }
+        let mut child = command.spawn()?;
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        std::thread::scope(|s| {
+            if let Some(mut out) = stdout {
+                s.spawn(move || tee(&mut out, false));
+            }
+            if let Some(mut err) = stderr {
+                s.spawn(move || tee(&mut err, true));
+            }
+        });
+
+        Ok(child.wait()?.code())
+    }
+
+    fn tcp_listeners(&self) -> io::Result<Vec<Listener>> {
+        crate::process::tcp_listeners(self)
+    }
+
+    fn process_info(&self, pid: u32) -> Option<ProcessInfo> {
+        crate::process::process_info(self, pid)
+    }
+}
+
+/// Scripted runner for tests. Responses are matched on the full command line
+/// (`"adb devices"`); unscripted commands fail as if the tool were not installed
+/// unless `allow_unscripted` is set. A streamed command that fails exits with 1. Path removals are matched as `"remove <path>"`
+/// and never touch the filesystem: scripted stdout is the number of bytes freed,
+/// a scripted failure is a permission error.
+/// Processes are scripted the same way: `"listeners"` as "<port> <pid>" lines,
+/// `"process <pid>"` as the command line optionally followed by the cwd, and
+/// signals as `"kill -TERM <pid>"`. A process exits once signalled, unless the
+/// signal is scripted to fail, which stands for a process that ignores it.
+/// Every invocation is recorded in order.
+#[cfg(test)]
+pub struct FakeRunner {
+    responses: std::collections::HashMap<String, CommandOutput>,
+    calls: std::cell::RefCell<Vec<String>>,
+    exited: std::cell::RefCell<Vec<u32>>,
+    allow_unscripted: bool,
+}
+
+#[cfg(test)]
+impl FakeRunner {
+    pub fn new() -> Self {
+        FakeRunner {
+            responses: std::collections::HashMap::new(),
+            calls: std::cell::RefCell::new(Vec::new()),
+            exited: std::cell::RefCell::new(Vec::new()),
+            allow_unscripted: false,
+        }
+    }
+
+    /// Let unscripted commands succeed with empty output instead of failing.
+    pub fn allow_unscripted(mut self) -> Self {
+        self.allow_unscripted = true;
+        self
+    }
+
+    /// Script a successful command with the given stdout.
+    pub fn on(mut self, command_line: &str, stdout: &str) -> Self {
+        self.responses.insert(command_line.to_string(), CommandOutput {
+            success: true,
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        });
+        self
+    }
+
+    /// Script a failing command with the given stderr.
+    pub fn on_fail(mut self, command_line: &str, stderr: &str) -> Self {
+        self.responses.insert(command_line.to_string(), CommandOutput {
+            success: false,
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        });
+        self
+    }
+
+    /// Command lines invoked so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    fn respond(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        let command_line = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.calls.borrow_mut().push(command_line.clone());
+
+        match self.responses.get(&command_line) {
+            Some(output) => Ok(output.clone()),
+            None if self.allow_unscripted => Ok(CommandOutput { success: true, ..Default::default() }),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: not scripted", program))),
+        }
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for FakeRunner {
+    fn output(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        self.respond(program, args)
+    }
+
+    fn status(&self, program: &str, args: &[&str]) -> io::Result<bool> {
+        self.respond(program, args).map(|output| output.success)
+    }
+
+    fn spawn(&self, program: &str, args: &[&str], _cwd: Option<&str>) -> io::Result<()> {
+        self.respond(program, args).map(|_| ())
+    }
+
+    fn stream(&self, program: &str, args: &[&str], _cwd: Option<&str>, tee: &StreamSink<'_>) -> io::Result<Option<i32>> {
+        let output = self.respond(program, args)?;
+        tee(&mut output.stdout.as_slice(), false);
+        tee(&mut output.stderr.as_slice(), true);
+        Ok(Some(if output.success { 0 } else { 1 }))
+    }
+
+    fn remove_path(&self, path: &Path) -> Result<Option<u64>> {
+        let path = path.to_string_lossy();
+        match self.respond("remove", &[&path]) {
+            Ok(output) if output.success => Ok(output.stdout_str().trim().parse().ok()),
+            Ok(_) => Err(crate::error::AppError::PermissionDenied(path.to_string())),
+            Err(e) => Err(crate::error::AppError::RemoveFailed(format!("{}: {}", path, e))),
+        }
+    }
+
+    fn tcp_listeners(&self) -> io::Result<Vec<Listener>> {
+        let output = self.respond("listeners", &[])?;
+        Ok(output
+            .stdout_str()
+            .lines()
+            .filter_map(|line| {
+                let (port, pid) = line.trim().split_once(' ')?;
+                Some(Listener { port: port.parse().ok()?, pid: pid.trim().parse().ok()? })
+            })
+            .collect())
+    }
+
+    fn process_info(&self, pid: u32) -> Option<ProcessInfo> {
+        if self.exited.borrow().contains(&pid) {
+            return None;
+        }
+        let output = self.respond("process", &[&pid.to_string()]).ok()?;
+        let stdout = output.stdout_str();
+        let mut lines = stdout.lines();
+        let command = lines.next().filter(|command| !command.is_empty())?.to_string();
+        let cwd = lines.next().map(|cwd| cwd.to_string());
+        Some(ProcessInfo { command, cwd })
+    }
+
+    fn send_signal(&self, pid: u32, signal: Signal) -> io::Result<()> {
+        let flag = format!("-{}", signal.name().trim_start_matches("SIG"));
+        let output = self.respond("kill", &[&flag, &pid.to_string()])?;
+        if output.success {
+            self.exited.borrow_mut().push(pid);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use serde::Deserialize;
+use crate::args::IosDeviceCommand;
+use crate::config::{Config, SimulatorSpec};
+use crate::diagnostics::list_simulators;
+use crate::error::{AppError, Result};
+use crate::output::{ProvisionResult, Simulator, SimulatorActionResult};
+use crate::runner::CommandRunner;
+use crate::terminal::is_macos;
+use crate::version::Version;
+
+#[derive(Deserialize)]
+struct SimctlRuntimes {
+    runtimes: Vec<SimctlRuntime>,
+}
+
+#[derive(Deserialize)]
+struct SimctlRuntime {
+    identifier: String,
+    name: String,
+    version: String,
+    #[serde(rename = "isAvailable")]
+    is_available: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct SimctlDeviceTypes {
+    devicetypes: Vec<SimctlDeviceType>,
+}
+
+#[derive(Deserialize)]
+struct SimctlDeviceType {
+    identifier: String,
+    name: String,
+}
+
+/// Result of a `devices ios` command.
+pub enum IosDeviceOutcome {
+    Action(SimulatorActionResult),
+    Provision(ProvisionResult),
+}
+
+/// Run a `devices ios` command. Simulators default to the configured one.
+pub fn execute(runner: &dyn CommandRunner, config: &Config, command: &IosDeviceCommand) -> Result<IosDeviceOutcome> {
+    if !is_macos() {
+        return Err(AppError::UnsupportedPlatform("iOS simulators are only available on macOS".to_string()));
+    }
+
+    let (simulator, changed, message) = match command {
+        IosDeviceCommand::Boot { simulator } => {
+            let simulator = resolve_simulator(runner, simulator.as_deref().unwrap_or(config.simulator()))?;
+            let changed = boot(runner, &simulator)?;
+            let message = if changed { "Booted" } else { "Already booted:" };
+            (simulator, changed, message)
+        }
+        IosDeviceCommand::Shutdown { simulator } => {
+            let simulator = resolve_simulator(runner, simulator.as_deref().unwrap_or(config.simulator()))?;
+            let changed = shutdown(runner, &simulator)?;
+            let message = if changed { "Shut down" } else { "Not running:" };
+            (simulator, changed, message)
+        }
+        IosDeviceCommand::Erase { simulator } => {
+            let simulator = resolve_simulator(runner, simulator.as_deref().unwrap_or(config.simulator()))?;
+            erase(runner, &simulator)?;
+            (simulator, true, "Erased")
+        }
+        IosDeviceCommand::Create { name, device_type, runtime } => {
+            let spec = SimulatorSpec {
+                name: name.clone(),
+                device_type: device_type.clone(),
+                runtime: runtime.clone(),
+            };
+            (create(runner, &spec)?, true, "Created")
+        }
+        IosDeviceCommand::Provision => {
+            if config.ios.simulators.is_empty() {
+                return Err(AppError::Config(
+                    "no simulators to provision; list them under [[ios.simulators]]".to_string(),
+                ));
+            }
+            return provision(runner, &config.ios.simulators).map(IosDeviceOutcome::Provision);
+        }
+    };
+
+    let message = format!("{} {} ({}, {})", message, simulator.name, runtime_label(&simulator.runtime), simulator.udid);
+    Ok(IosDeviceOutcome::Action(SimulatorActionResult {
+        action: command.name().to_string(),
+        simulator,
+        changed,
+        message,
+    }))
+}
+
+/// Find an iOS simulator by UDID or name. A name shared by simulators on
+/// several runtimes is ambiguous and must be given as a UDID instead.
+pub fn resolve_simulator(runner: &dyn CommandRunner, name_or_udid: &str) -> Result<Simulator> {
+    let simulators = list_simulators(runner).simulators;
+
+    if let Some(simulator) = simulators.iter().find(|sim| sim.udid.eq_ignore_ascii_case(name_or_udid)) {
+        return Ok(simulator.clone());
+    }
+
+    let mut matches: Vec<&Simulator> = simulators.iter().filter(|sim| sim.name == name_or_udid).collect();
+    matches.sort_by(|a, b| a.runtime.cmp(&b.runtime));
+    match matches.len() {
+        0 => Err(AppError::SimulatorNotFound(name_or_udid.to_string())),
+        1 => Ok(matches.remove(0).clone()),
+        _ => Err(AppError::AmbiguousDevice(
+            name_or_udid.to_string(),
+            matches.iter().map(|sim| format!("{} ({})", sim.udid, runtime_label(&sim.runtime))).collect(),
+        )),
+    }
+}
+
+/// "com.apple.CoreSimulator.SimRuntime.iOS-17-2" -> "iOS 17.2"
+pub fn runtime_label(runtime: &str) -> String {
+    let short = runtime.rsplit('.').next().unwrap_or(runtime);
+    match short.split_once('-') {
+        Some((platform, version)) => format!("{} {}", platform, version.replace('-', ".")),
+        None => short.to_string(),
+    }
+}
+
+fn simctl(runner: &dyn CommandRunner, args: &[&str]) -> Result<String> {
+    let command = format!("xcrun simctl {}", args.join(" "));
+    let output = runner
+        .output("xcrun", &[&["simctl"], args].concat())
+        .map_err(|_| AppError::CommandFailed(command.clone()))?;
+
+    if !output.success {
+        let stderr = output.stderr_str();
+        let reason = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("").trim();
+        return Err(AppError::CommandFailed(format!("{}: {}", command, reason)));
+    }
+    Ok(output.stdout_str())
+}
+
+/// Boot a simulator. Returns false if it was already booted.
+pub fn boot(runner: &dyn CommandRunner, simulator: &Simulator) -> Result<bool> {
+    if simulator.state == "Booted" {
+        return Ok(false);
+    }
+    simctl(runner, &["boot", &simulator.udid])?;
+    Ok(true)
+}
+
+/// Shut a simulator down. Returns false if it was not running.
+pub fn shutdown(runner: &dyn CommandRunner, simulator: &Simulator) -> Result<bool> {
+    if simulator.state == "Shutdown" {
+        return Ok(false);
+    }
+    simctl(runner, &["shutdown", &simulator.udid])?;
+    Ok(true)
+}
+
+/// Erase a simulator's contents and settings, shutting it down first since
+/// simctl refuses to erase a booted device.
+pub fn erase(runner: &dyn CommandRunner, simulator: &Simulator) -> Result<()> {
+    shutdown(runner, simulator)?;
+    simctl(runner, &["erase", &simulator.udid])?;
+    Ok(())
+}
+
+/// Create a simulator. The device type defaults to the name ("iPhone 15 Pro"),
+/// the runtime to the newest available iOS runtime.
+pub fn create(runner: &dyn CommandRunner, spec: &SimulatorSpec) -> Result<Simulator> {
+    let device_type = resolve_device_type(runner, spec.device_type.as_deref().unwrap_or(&spec.name))?;
+    let runtime = resolve_runtime(runner, spec.runtime.as_deref())?;
+
+    let udid = simctl(runner, &["create", &spec.name, &device_type, &runtime])?;
+    Ok(Simulator {
+        name: spec.name.clone(),
+        udid: udid.trim().to_string(),
+        state: "Shutdown".to_string(),
+        runtime,
+    })
+}
+
+/// Create every simulator in the configured set that doesn't exist yet.
+/// A simulator exists when one with the same name is on the requested runtime
+/// (or on any runtime, when none is requested).
+pub fn provision(runner: &dyn CommandRunner, specs: &[SimulatorSpec]) -> Result<ProvisionResult> {
+    let simulators = list_simulators(runner).simulators;
+    let mut created = Vec::new();
+    let mut existing = Vec::new();
+
+    for spec in specs {
+        let runtime = match &spec.runtime {
+            Some(runtime) => Some(resolve_runtime(runner, Some(runtime))?),
+            None => None,
+        };
+        let found = simulators.iter().find(|sim| {
+            sim.name == spec.name && runtime.as_ref().is_none_or(|runtime| &sim.runtime == runtime)
+        });
+
+        match found {
+            Some(simulator) => existing.push(simulator.clone()),
+            None => created.push(create(runner, spec)?),
+        }
+    }
+
+    let message = format!("Created {} simulator(s), {} already present", created.len(), existing.len());
+    Ok(ProvisionResult { created, existing, message })
+}
+
+/// Runtime identifier for an identifier, name ("iOS 17.2"), compact name
+/// ("iOS17.2") or version ("17.2"); the newest available iOS runtime for None.
+fn resolve_runtime(runner: &dyn CommandRunner, wanted: Option<&str>) -> Result<String> {
+    let json = simctl(runner, &["list", "runtimes", "--json"])?;
+    let runtimes: Vec<SimctlRuntime> = serde_json::from_str::<SimctlRuntimes>(&json)?
+        .runtimes
+        .into_iter()
+        .filter(|runtime| runtime.is_available.unwrap_or(true))
+        .collect();
+
+    let found = match wanted {
+        Some(wanted) => runtimes.iter().find(|runtime| {
+            runtime.identifier == wanted
+                || runtime.name.eq_ignore_ascii_case(wanted)
+                || runtime.name.replace(' ', "").eq_ignore_ascii_case(wanted)
+                || runtime.version == wanted
+        }),
+        None => runtimes
+            .iter()
+            .filter(|runtime| runtime.name.starts_with("iOS"))
+            .max_by_key(|runtime| Version::parse(&runtime.version).ok().map(|v| (v.major, v.minor, v.patch))),
+    };
+
+    found.map(|runtime| runtime.identifier.clone()).ok_or_else(|| {
+        AppError::SimulatorSetup(match wanted {
+            Some(wanted) => format!("no available runtime matches '{}'", wanted),
+            None => "no iOS runtime is installed".to_string(),
+        })
+    })
+}
+
+/// Device type identifier for an identifier or name ("iPhone 15 Pro").
+fn resolve_device_type(runner: &dyn CommandRunner, wanted: &str) -> Result<String> {
+    let json = simctl(runner, &["list", "devicetypes", "--json"])?;
+    serde_json::from_str::<SimctlDeviceTypes>(&json)?
+        .devicetypes
+        .into_iter()
+        .find(|device_type| device_type.identifier == wanted || device_type.name.eq_ignore_ascii_case(wanted))
+        .map(|device_type| device_type.identifier)
+        .ok_or_else(|| AppError::SimulatorSetup(format!("no device type matches '{}'", wanted)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    fn simctl_runner() -> FakeRunner {
+        let devices = r#"{"devices": {
+            "com.apple.CoreSimulator.SimRuntime.iOS-17-2": [
+                {"name": "iPhone 15", "udid": "A1", "state": "Booted", "isAvailable": true},
+                {"name": "iPad Air", "udid": "B1", "state": "Shutdown", "isAvailable": true}
+            ],
+            "com.apple.CoreSimulator.SimRuntime.iOS-16-4": [
+                {"name": "iPhone 15", "udid": "A2", "state": "Shutdown", "isAvailable": true}
+            ]
+        }}"#;
+        let runtimes = r#"{"runtimes": [
+            {"identifier": "com.apple.CoreSimulator.SimRuntime.iOS-16-4", "name": "iOS 16.4", "version": "16.4", "isAvailable": true},
+            {"identifier": "com.apple.CoreSimulator.SimRuntime.iOS-17-2", "name": "iOS 17.2", "version": "17.2", "isAvailable": true},
+            {"identifier": "com.apple.CoreSimulator.SimRuntime.watchOS-10-2", "name": "watchOS 10.2", "version": "10.2", "isAvailable": true}
+        ]}"#;
+        let devicetypes = r#"{"devicetypes": [
+            {"identifier": "com.apple.CoreSimulator.SimDeviceType.iPhone-15-Pro", "name": "iPhone 15 Pro"}
+        ]}"#;
+        FakeRunner::new()
+            .on("xcrun simctl list devices --json", devices)
+            .on("xcrun simctl list runtimes --json", runtimes)
+            .on("xcrun simctl list devicetypes --json", devicetypes)
+            .on("xcrun simctl shutdown A1", "")
+            .on("xcrun simctl erase A1", "")
+            .on("xcrun simctl create iPhone 15 Pro com.apple.CoreSimulator.SimDeviceType.iPhone-15-Pro com.apple.CoreSimulator.SimRuntime.iOS-17-2", "C1\n")
+    }
+
+    #[test]
+    fn test_resolve_simulator_by_name_or_udid() {
+        let runner = simctl_runner();
+        // Names shared across runtimes need a UDID; UDIDs match case-insensitively
+        assert!(matches!(resolve_simulator(&runner, "iPhone 15"), Err(AppError::AmbiguousDevice(_, matches)) if matches.len() == 2));
+        assert_eq!(resolve_simulator(&runner, "a2").unwrap().runtime, "com.apple.CoreSimulator.SimRuntime.iOS-16-4");
+        assert!(matches!(resolve_simulator(&runner, "iPhone 99"), Err(AppError::SimulatorNotFound(_))));
+    }
+
+    #[test]
+    fn test_boot_and_erase_booted_simulator() {
+        let runner = simctl_runner();
+        // Booting a booted simulator is a no-op; erasing shuts it down first
+        let booted = resolve_simulator(&runner, "A1").unwrap();
+        assert!(!boot(&runner, &booted).unwrap());
+        erase(&runner, &booted).unwrap();
+        assert!(runner.calls().ends_with(&["xcrun simctl shutdown A1".to_string(), "xcrun simctl erase A1".to_string()]));
+    }
+
+    #[test]
+    fn test_provision_creates_only_missing_simulators() {
+        let runner = simctl_runner();
+        // On the newest iOS runtime by default
+        let config: Config = toml::from_str(r#"
+            [[ios.simulators]]
+            name = "iPad Air"
+            runtime = "iOS 17.2"
+            [[ios.simulators]]
+            name = "iPhone 15 Pro"
+        "#).unwrap();
+        let result = provision(&runner, &config.ios.simulators).unwrap();
+        assert_eq!(result.existing.len(), 1);
+        assert_eq!(result.created.len(), 1);
+        assert_eq!(result.created[0].udid, "C1");
+        assert_eq!(runtime_label(&result.created[0].runtime), "iOS 17.2");
+    }
+
+    #[test]
+    fn test_create_rejects_unknown_device_type() {
+        let spec = SimulatorSpec { name: "Watch".to_string(), device_type: Some("Apple Watch".to_string()), runtime: None };
+        assert!(matches!(create(&simctl_runner(), &spec), Err(AppError::SimulatorSetup(_))));
+    }
+}
@@ -0,0 +1,195 @@
+use std::env;
+use crate::error::{AppError, Result};
+use crate::runner::CommandRunner;
+
+/// Terminal used to open the packager and build commands in a new window.
+/// macOS drives Terminal.app via osascript; Linux hosts use whichever
+/// supported terminal is available.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerminalLauncher {
+    MacTerminal,
+    Tmux,
+    GnomeTerminal,
+    Konsole,
+    Xterm,
+}
+
+impl TerminalLauncher {
+    /// Detect the terminal to use on this host.
+    /// RN_RUN_TERMINAL forces a specific launcher; otherwise inside a tmux
+    /// session a new tmux window is preferred over a GUI terminal.
+    pub fn detect() -> Result<Self> {
+        if let Ok(name) = env::var("RN_RUN_TERMINAL") {
+            return Self::from_name(&name).ok_or(AppError::TerminalNotFound);
+        }
+
+        if is_macos() {
+            return Ok(TerminalLauncher::MacTerminal);
+        }
+
+        if env::var("TMUX").is_ok() && command_exists("tmux") {
+            return Ok(TerminalLauncher::Tmux);
+        }
+
+        let has_display = env::var("DISPLAY").is_ok() || env::var("WAYLAND_DISPLAY").is_ok();
+        if has_display {
+            for launcher in [TerminalLauncher::GnomeTerminal, TerminalLauncher::Konsole, TerminalLauncher::Xterm] {
+                if command_exists(launcher.name()) {
+                    return Ok(launcher);
+                }
+            }
+        }
+
+        // Fall back to tmux outside a session; it creates a detached server window
+        if command_exists("tmux") {
+            return Ok(TerminalLauncher::Tmux);
+        }
+
+        Err(AppError::TerminalNotFound)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            TerminalLauncher::MacTerminal,
+            TerminalLauncher::Tmux,
+            TerminalLauncher::GnomeTerminal,
+            TerminalLauncher::Konsole,
+            TerminalLauncher::Xterm,
+        ]
+        .into_iter()
+        .find(|launcher| launcher.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TerminalLauncher::MacTerminal => "Terminal",
+            TerminalLauncher::Tmux => "tmux",
+            TerminalLauncher::GnomeTerminal => "gnome-terminal",
+            TerminalLauncher::Konsole => "konsole",
+            TerminalLauncher::Xterm => "xterm",
+        }
+    }
+
+    /// Run `command` in a new terminal window with `cwd` as working directory.
+    /// The window stays open after the command exits so output can be read.
+    pub fn open(&self, runner: &dyn CommandRunner, cwd: &str, command: &str) -> Result<()> {
+        let keep_open = format!("{}; exec $SHELL", command);
+
+        match self {
+            TerminalLauncher::MacTerminal => {
+                // Quote for the shell, then escape for the AppleScript string
+                let shell_command = format!("cd {}; {}", shell_quote(cwd), command);
+                let script = format!(
+                    "tell application \"Terminal\" to do script \"{}\"",
+                    shell_command.replace('\\', "\\\\").replace('"', "\\\"")
+                );
+                runner
+                    .status("osascript", &["-e", &script])
+                    .map_err(|_| AppError::CommandFailed("osascript do script".to_string()))?;
+            }
+            TerminalLauncher::Tmux => {
+                let tmux = |args: &[&str]| {
+                    runner
+                        .status("tmux", args)
+                        .map_err(|e| AppError::ProcessError(format!("failed to launch tmux: {}", e)))
+                };
+
+                // Not attached to a server yet: start a detached session, or add a
+                // window to the one an earlier run left behind
+                let opened = tmux(&["new-window", "-n", "rn-run", "-c", cwd, &keep_open])?
+                    || tmux(&["new-session", "-d", "-s", "rn-run", "-c", cwd, &keep_open])?
+                    || tmux(&["new-window", "-t", "rn-run:", "-n", "rn-run", "-c", cwd, &keep_open])?;
+                if !opened {
+                    return Err(AppError::CommandFailed("tmux new-session -s rn-run".to_string()));
+                }
+            }
+            TerminalLauncher::GnomeTerminal => {
+                let working_dir = format!("--working-directory={}", cwd);
+                runner
+                    .spawn("gnome-terminal", &[&working_dir, "--", "sh", "-c", &keep_open], None)
+                    .map_err(|e| AppError::ProcessError(format!("failed to launch gnome-terminal: {}", e)))?;
+            }
+            TerminalLauncher::Konsole => {
+                runner
+                    .spawn("konsole", &["--workdir", cwd, "-e", "sh", "-c", &keep_open], None)
+                    .map_err(|e| AppError::ProcessError(format!("failed to launch konsole: {}", e)))?;
+            }
+            TerminalLauncher::Xterm => {
+                runner
+                    .spawn("xterm", &["-T", "rn-run", "-e", "sh", "-c", &keep_open], Some(cwd))
+                    .map_err(|e| AppError::ProcessError(format!("failed to launch xterm: {}", e)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Stand-in for `TerminalLauncher::open` during a dry run: records what the
+/// new window would run, without probing the host for a terminal.
+pub fn record_terminal_launch(runner: &dyn CommandRunner, cwd: &str, command: &str) -> Result<()> {
+    let shell_command = format!("cd {}; {}", shell_quote(cwd), command);
+    runner
+        .spawn("sh", &["-c", &shell_command], Some(cwd))
+        .map_err(|e| AppError::ProcessError(format!("failed to record terminal launch: {}", e)))
+}
+
+pub fn is_macos() -> bool {
+    cfg!(target_os = "macos")
+}
+
+/// Check whether an executable is available on PATH.
+pub fn command_exists(cmd: &str) -> bool {
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(cmd).is_file()))
+        .unwrap_or(false)
+}
+
+/// Quote a string for use as a single POSIX shell word.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace("'", "'\"'\"'"))
+}
+
+/// Wrap `command` with `script` so its output is appended to `log_path`
+/// while keeping full TTY behavior (colors, animations, spinners), exiting
+/// with the command's status.
+/// BSD `script` (macOS) and util-linux `script` take different arguments.
+pub fn script_command(log_path: &str, command: &str) -> String {
+    if is_macos() {
+        format!("script -q -a {} bash -c {}", shell_quote(log_path), shell_quote(command))
+    } else {
+        format!("script -q -e -a -c {} {}", shell_quote(command), shell_quote(log_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::FakeRunner;
+
+    #[test]
+    fn test_mac_terminal_quotes_working_directory() {
+        let runner = FakeRunner::new().allow_unscripted();
+        TerminalLauncher::MacTerminal.open(&runner, "/Users/me/My \"App\" it's", "yarn start").unwrap();
+        assert_eq!(runner.calls(), [
+            r#"osascript -e tell application "Terminal" to do script "cd '/Users/me/My \"App\" it'\"'\"'s'; yarn start""#,
+        ]);
+    }
+
+    #[test]
+    fn test_tmux_reuses_a_detached_rn_run_session() {
+        let runner = FakeRunner::new()
+            .on_fail("tmux new-window -n rn-run -c /app yarn start; exec $SHELL", "no server running")
+            .on_fail("tmux new-session -d -s rn-run -c /app yarn start; exec $SHELL", "duplicate session: rn-run")
+            .on("tmux new-window -t rn-run: -n rn-run -c /app yarn start; exec $SHELL", "");
+        TerminalLauncher::Tmux.open(&runner, "/app", "yarn start").unwrap();
+        assert_eq!(runner.calls().len(), 3);
+
+        // Nothing to attach to and no session could be started
+        let runner = FakeRunner::new()
+            .on_fail("tmux new-window -n rn-run -c /app yarn start; exec $SHELL", "")
+            .on_fail("tmux new-session -d -s rn-run -c /app yarn start; exec $SHELL", "")
+            .on_fail("tmux new-window -t rn-run: -n rn-run -c /app yarn start; exec $SHELL", "");
+        assert!(matches!(TerminalLauncher::Tmux.open(&runner, "/app", "yarn start"), Err(AppError::CommandFailed(_))));
+    }
+}
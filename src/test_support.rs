@@ -0,0 +1,101 @@
+//! Helpers shared by the unit tests in each module.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Serializes tests that change process-wide environment variables.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// A fresh directory under the system temp dir, removed again when dropped.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "rn-run-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, relative: impl AsRef<Path>) -> PathBuf {
+        self.path.join(relative)
+    }
+
+    /// Write `contents` to `relative`, creating its parent directories.
+    pub fn write(&self, relative: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Holds `ENV_LOCK` while a test reads or changes environment variables, and
+/// puts them back when dropped, also when the test panics.
+pub struct EnvGuard {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    pub fn lock() -> Self {
+        EnvGuard { saved: Vec::new(), _lock: ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner()) }
+    }
+
+    /// Lock and point HOME (and so the log directory and user config) at `home`.
+    pub fn home(home: &Path) -> Self {
+        let mut env = Self::lock();
+        env.set("HOME", home);
+        env.remove("XDG_CONFIG_HOME");
+        env
+    }
+
+    pub fn set(&mut self, key: &'static str, value: impl AsRef<OsStr>) {
+        self.save(key);
+        env::set_var(key, value);
+    }
+
+    pub fn remove(&mut self, key: &'static str) {
+        self.save(key);
+        env::remove_var(key);
+    }
+
+    fn save(&mut self, key: &'static str) {
+        if !self.saved.iter().any(|(saved, _)| *saved == key) {
+            self.saved.push((key, env::var_os(key)));
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, value) in self.saved.drain(..).rev() {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key),
+            }
+        }
+    }
+}
@@ -1,21 +1,28 @@
-use std::{env, fs, fs::File, io::Read, path::PathBuf, process::Command, process::Stdio};
-use crate::args::Args;
+use std::{env, fs, fs::File, io, io::Read, io::Seek, path::Path, path::PathBuf};
+use std::time::{Duration, Instant};
+use crate::config::Config;
+use crate::diagnostics::{list_android_devices, list_ios_devices};
 use crate::error::{AppError, Result};
-use serde::Deserialize;
+use crate::metro;
+use crate::output::{AndroidDevice, BuildResult, KilledProcess, MetroReadiness};
+use crate::process::{self, Signal};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use crate::args::LogFilterArgs;
 use chrono::Local;
+use crate::package_manager::PackageManager;
+use crate::runner::{CommandOutput, CommandRunner};
+use crate::version::Version;
+use crate::terminal::{is_macos, record_terminal_launch, script_command, shell_quote, TerminalLauncher};
 
 const LOG_DIR: &str = ".rn-run/logs";
-const MAX_LOGS: usize = 10;
 
 /// Get the preferred Android device, prioritizing physical devices over emulators.
 /// Returns the device ID if a physical device is found, None otherwise.
-pub fn get_preferred_android_device() -> Option<String> {
-    let output = Command::new("adb")
-        .args(["devices"])
-        .output()
-        .ok()?;
+pub fn get_preferred_android_device(runner: &dyn CommandRunner) -> Option<String> {
+    let output = runner.output("adb", &["devices"]).ok()?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = output.stdout_str();
     let mut physical_device: Option<String> = None;
     let mut emulator_device: Option<String> = None;
 
@@ -39,6 +46,106 @@ pub fn get_preferred_android_device() -> Option<String> {
     physical_device.or(emulator_device)
 }
 
+/// Find the connected Android device `wanted` names: a serial, or a model or
+/// device codename as shown by `adb devices -l` ("Pixel 7", "SM_G973F", "panther").
+pub fn resolve_android_device(runner: &dyn CommandRunner, wanted: &str) -> Result<String> {
+    let devices = list_android_devices(runner);
+    let normalize = |name: &str| name.replace('_', " ").to_lowercase();
+
+    let matches: Vec<&AndroidDevice> = match devices.iter().find(|device| device.serial == wanted) {
+        Some(device) => vec![device],
+        None => devices
+            .iter()
+            .filter(|device| {
+                [&device.model, &device.device]
+                    .into_iter()
+                    .flatten()
+                    .any(|name| normalize(name) == normalize(wanted))
+            })
+            .collect(),
+    };
+
+    match matches.as_slice() {
+        [] => Err(AppError::AndroidDeviceNotFound(wanted.to_string())),
+        [device] if device.state == "device" => Ok(device.serial.clone()),
+        [device] => Err(AppError::AndroidDeviceUnavailable(device.serial.clone(), device.state.clone())),
+        _ => Err(AppError::AmbiguousDevice(
+            wanted.to_string(),
+            matches.iter().map(|device| device.serial.clone()).collect(),
+        )),
+    }
+}
+
+/// Ask on the terminal which of the ready `devices` to install on.
+/// Returns None when there is nothing to choose between or the answer is invalid.
+pub fn pick_android_device(devices: &[AndroidDevice]) -> Option<String> {
+    let ready: Vec<&AndroidDevice> = devices.iter().filter(|device| device.state == "device").collect();
+    if ready.len() < 2 {
+        return None;
+    }
+
+    eprintln!("Several Android devices are connected:");
+    for (index, device) in ready.iter().enumerate() {
+        let name = device.model.as_deref().or(device.avd.as_deref()).unwrap_or("unknown model");
+        eprintln!("  {}) {} ({})", index + 1, name, device.serial);
+    }
+    eprint!("Install on which device? [1-{}]: ", ready.len());
+    let _ = io::Write::flush(&mut io::stderr());
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok()?;
+    let choice = answer.trim().parse::<usize>().ok()?;
+    ready.get(choice.checked_sub(1)?).map(|device| device.serial.clone())
+}
+
+/// Where `run ios` installs the app.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IosTarget {
+    /// A connected physical device, by UDID
+    Device(String),
+    /// A configured device that isn't connected; the react-native CLI looks it up by name
+    NamedDevice(String),
+    Simulator(String),
+}
+
+impl IosTarget {
+    /// The run-ios flag selecting this target.
+    pub fn flag(&self) -> String {
+        match self {
+            IosTarget::Device(udid) => format!("--udid={}", udid),
+            IosTarget::NamedDevice(name) => format!("--device=\"{}\"", name),
+            IosTarget::Simulator(name) => format!("--simulator=\"{}\"", name),
+        }
+    }
+}
+
+/// Pick the iOS target, mirroring the Android preference: a configured device
+/// wins, then a configured simulator, then a connected physical device, and
+/// finally the default simulator.
+pub fn get_ios_target(runner: &dyn CommandRunner, config: &Config) -> IosTarget {
+    if let Some(wanted) = &config.ios.device {
+        return list_ios_devices(runner)
+            .into_iter()
+            .find(|device| device.udid.eq_ignore_ascii_case(wanted) || device.name == *wanted)
+            .map(|device| IosTarget::Device(device.udid))
+            .unwrap_or_else(|| IosTarget::NamedDevice(wanted.clone()));
+    }
+    if config.ios.simulator.is_some() {
+        return IosTarget::Simulator(config.simulator().to_string());
+    }
+
+    // Prefer a cabled device over one reached over Wi-Fi
+    let mut connected: Vec<_> = list_ios_devices(runner)
+        .into_iter()
+        .filter(|device| device.state == "connected")
+        .collect();
+    connected.sort_by_key(|device| device.transport.as_deref() != Some("usb"));
+    match connected.into_iter().next() {
+        Some(device) => IosTarget::Device(device.udid),
+        None => IosTarget::Simulator(config.simulator().to_string()),
+    }
+}
+
 pub fn get_current_directory_logged(log: Option<&LogWriter>) -> Result<String> {
     let current_dir = env::current_dir()?
         .to_str()
@@ -59,73 +166,68 @@ pub fn get_current_directory() -> Result<String> {
     get_current_directory_logged(None)
 }
 
-pub fn kill_process_logged(log: Option<&LogWriter>) -> Result<()> {
-    let output = Command::new("lsof")
-        .arg("-i")
-        .arg(":8081")
-        .arg("-t")
-        .output()
-        .map_err(|_| AppError::CommandFailed("lsof -i :8081 -t".to_string()))?;
-
-    let pids_result: std::result::Result<Vec<u32>, _> = String::from_utf8_lossy(&output.stdout)
-        .split_whitespace()
-        .map(|pid| pid.parse::<u32>())
-        .collect();
+/// How long Metro gets to shut down after SIGTERM before it is sent SIGKILL.
+const METRO_TERM_GRACE: Duration = Duration::from_secs(5);
+
+/// Stop the Metro listening on `port`: SIGTERM, then SIGKILL if it is still running
+/// after a grace period. Only the listener is matched, so clients connected to the
+/// port (an emulator, a browser) are left alone, and a listener that isn't Node
+/// fails with `PortInUse` instead of being killed. Returns the stopped processes.
+pub fn kill_process_logged(runner: &dyn CommandRunner, port: u16, log: Option<&LogWriter>) -> Result<Vec<KilledProcess>> {
+    let say = |msg: &str| match log {
+        Some(log) => log.log(msg),
+        None => println!("{}", msg),
+    };
 
-    let pids = match pids_result {
-        Ok(pids) => pids,
-        Err(_) => {
-            let msg = "No process running on port 8081";
-            if let Some(log) = log {
-                log.log(msg);
-            } else {
-                println!("{}", msg);
-            }
-            return Ok(());
+    let listeners = runner
+        .tcp_listeners()
+        .map_err(|e| AppError::ProcessError(format!("failed to list processes on port {}: {}", port, e)))?;
+
+    let mut killed = Vec::new();
+    for listener in listeners.iter().filter(|listener| listener.port == port) {
+        // It may have exited since the listeners were read
+        let info = match runner.process_info(listener.pid) {
+            Some(info) => info,
+            None => continue,
+        };
+        if !process::is_metro_command(&info.command) {
+            return Err(AppError::PortInUse(port, format!("{} (pid {})", info.command, listener.pid)));
         }
-    };
 
-    if pids.is_empty() {
-        let msg = "No process running on port 8081";
-        if let Some(log) = log {
-            log.log(msg);
-        } else {
-            println!("{}", msg);
+        let signal = process::terminate(runner, listener.pid, METRO_TERM_GRACE)
+            .map_err(|e| AppError::ProcessError(format!("failed to stop pid {}: {}", listener.pid, e)))?;
+        if signal == Signal::Kill {
+            say(&format!("pid {} ignored SIGTERM and was killed", listener.pid));
         }
+        killed.push(KilledProcess {
+            pid: listener.pid,
+            command: info.command,
+            signal: signal.name().to_string(),
+        });
+    }
+
+    if killed.is_empty() {
+        say(&format!("No process running on port {}", port));
     } else {
-        let count = pids.len();
-        for pid in pids {
-            Command::new("kill")
-                .arg(pid.to_string())
-                .spawn()
-                .map_err(|_| AppError::CommandFailed(format!("kill {}", pid)))?;
-        }
-        let msg = format!("Killed {} Metro process(es) on port 8081", count);
-        if let Some(log) = log {
-            log.log(&msg);
-        } else {
-            println!("{}", msg);
-        }
+        say(&format!("Killed {} Metro process(es) on port {}", killed.len(), port));
     }
 
-    Ok(())
+    Ok(killed)
 }
 
-pub fn kill_process() -> Result<()> {
-    kill_process_logged(None)
+pub fn kill_process(runner: &dyn CommandRunner, port: u16) -> Result<Vec<KilledProcess>> {
+    kill_process_logged(runner, port, None)
 }
 
-pub fn quit_simulator() -> Result<()> {
-    Command::new("osascript")
-        .arg("-e")
-        .arg("tell application \"Simulator\" to quit")
-        .status()
+pub fn quit_simulator(runner: &dyn CommandRunner) -> Result<()> {
+    runner
+        .status("osascript", &["-e", "tell application \"Simulator\" to quit"])
         .map_err(|_| AppError::CommandFailed("osascript quit simulator".to_string()))?;
 
     Ok(())
 }
 
-pub fn take_ios_screenshot(output_path: Option<&str>) -> Result<String> {
+pub fn take_ios_screenshot(runner: &dyn CommandRunner, output_path: Option<&str>) -> Result<String> {
     let path = match output_path {
         Some(p) => p.to_string(),
         None => {
@@ -137,13 +239,12 @@ pub fn take_ios_screenshot(output_path: Option<&str>) -> Result<String> {
         }
     };
 
-    let output = Command::new("xcrun")
-        .args(["simctl", "io", "booted", "screenshot", &path])
-        .output()
+    let output = runner
+        .output("xcrun", &["simctl", "io", "booted", "screenshot", &path])
         .map_err(|_| AppError::CommandFailed("xcrun simctl io booted screenshot".to_string()))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.success {
+        let stderr = output.stderr_str();
         return Err(AppError::CommandFailed(format!(
             "Screenshot failed: {}. Is an iOS simulator running?",
             stderr.trim()
@@ -153,7 +254,7 @@ pub fn take_ios_screenshot(output_path: Option<&str>) -> Result<String> {
     Ok(path)
 }
 
-pub fn take_android_screenshot(output_path: Option<&str>) -> Result<String> {
+pub fn take_android_screenshot(runner: &dyn CommandRunner, output_path: Option<&str>) -> Result<String> {
     let path = match output_path {
         Some(p) => p.to_string(),
         None => {
@@ -166,17 +267,16 @@ pub fn take_android_screenshot(output_path: Option<&str>) -> Result<String> {
     };
 
     // Use adb to capture screenshot and pull to local path
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(format!(
-            "adb exec-out screencap -p > \"{}\"",
-            path.replace("\"", "\\\"")
-        ))
-        .output()
+    let capture = format!(
+        "adb exec-out screencap -p > \"{}\"",
+        path.replace("\"", "\\\"")
+    );
+    let output = runner
+        .output("sh", &["-c", &capture])
         .map_err(|_| AppError::CommandFailed("adb exec-out screencap".to_string()))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.success {
+        let stderr = output.stderr_str();
         return Err(AppError::CommandFailed(format!(
             "Screenshot failed: {}. Is an Android emulator/device connected?",
             stderr.trim()
@@ -194,11 +294,9 @@ pub fn take_android_screenshot(output_path: Option<&str>) -> Result<String> {
     Ok(path)
 }
 
-pub fn close_terminal_windows() -> Result<()> {
-    Command::new("osascript")
-        .arg("-e")
-        .arg("tell application \"Terminal\" to close (every window)")
-        .status()
+pub fn close_terminal_windows(runner: &dyn CommandRunner) -> Result<()> {
+    runner
+        .status("osascript", &["-e", "tell application \"Terminal\" to close (every window)"])
         .map_err(|_| AppError::CommandFailed("osascript close terminal windows".to_string()))?;
     
     Ok(())
@@ -254,99 +352,122 @@ pub fn has_prebuild_script(path: &PathBuf) -> bool {
         .unwrap_or(false)
 }
 
-pub fn clean_install(react_native_version: &str, platform: &str) -> Result<()> {
+/// Delete a file or directory tree without going through a shell.
+/// Returns the bytes freed, or None when there was nothing at `path`.
+pub fn remove_path(path: &Path) -> Result<Option<u64>> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(removal_error(path, e)),
+    };
+
+    let bytes = path_size(path);
+    let removed = if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    removed.map_err(|e| removal_error(path, e))?;
+
+    Ok(Some(bytes))
+}
 
-    let command = if is_version_greater_or_equal(react_native_version, "0.74") {
-        "npm"
-    } else if react_native_version.starts_with("0.69") {
-        "yarn"
+fn removal_error(path: &Path, error: io::Error) -> AppError {
+    let path = path.to_string_lossy().to_string();
+    if error.kind() == io::ErrorKind::PermissionDenied {
+        AppError::PermissionDenied(path)
     } else {
-        "yarn"
+        AppError::RemoveFailed(format!("{}: {}", path, error))
+    }
+}
+
+/// Total size of a file, or of everything under a directory. Symlinks are not followed.
+pub fn path_size(path: &Path) -> u64 {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
     };
 
-    Command::new("rm")
-        .arg("-rf")
-        .arg("node_modules")
-        .status()
-        .map_err(|_| AppError::CommandFailed("rm -rf node_modules".to_string()))?;
-    println!("\x1b[32m[rn-run]: node_modules deleted\x1b[0m");
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| path_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Human readable size, e.g. "1.4 GB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Remove `path` through the runner and log what was freed.
+fn remove_logged(runner: &dyn CommandRunner, path: &str, log: &LogWriter) -> Result<()> {
+    match runner.remove_path(Path::new(path))? {
+        Some(bytes) => log.log_green(&format!("[rn-run]: {} deleted ({} freed)", path, format_bytes(bytes))),
+        None => log.log_green(&format!("[rn-run]: {} already absent", path)),
+    }
+    Ok(())
+}
+
+pub fn clean_install(runner: &dyn CommandRunner, package_manager: PackageManager, platform: &str, log: &LogWriter) -> Result<()> {
+    let command = package_manager.name();
 
-    Command::new(command)
-        .arg("install")
-        .status()
+    remove_logged(runner, "node_modules", log)?;
+
+    let installed = runner
+        .status(command, &["install"])
         .map_err(|_| AppError::CommandFailed(format!("{} install", command)))?;
-    println!("\x1b[32m[rn-run]: {} install completed\x1b[0m", command);
+    if !installed {
+        return Err(AppError::CommandFailed(format!("{} install failed", command)));
+    }
+    log.log_green(&format!("[rn-run]: {} install completed", command));
 
     if platform == "ios" {
-        Command::new("sh")
-            .arg("-c")
-            .arg("cd ios && pod install && cd ..")
-            .status()
+        let installed = runner
+            .status("sh", &["-c", "cd ios && pod install && cd .."])
             .map_err(|_| AppError::CommandFailed("pod install".to_string()))?;
-        println!("\x1b[32m[rn-run]: pod install completed\x1b[0m");
+        if !installed {
+            return Err(AppError::CommandFailed("pod install failed".to_string()));
+        }
+        log.log_green("[rn-run]: pod install completed");
     }
 
     Ok(())
 }
 
-pub fn deep_clean(platform: &str) -> Result<()> {
-    println!("\x1b[32m[rn-run]: starting deep clean for {}\x1b[0m", platform);
+pub fn deep_clean(runner: &dyn CommandRunner, package_manager: PackageManager, platform: &str, log: &LogWriter) -> Result<()> {
+    log.log_green(&format!("[rn-run]: starting deep clean for {}", platform));
 
-    Command::new("rm")
-        .arg("-rf")
-        .arg("package-lock.json")
-        .status()
-        .map_err(|_| AppError::CommandFailed("rm -rf package-lock.json".to_string()))?;
-    println!("\x1b[32m[rn-run]: package-lock.json deleted\x1b[0m");
+    remove_logged(runner, package_manager.lockfile(), log)?;
 
     if platform == "ios" {
-        Command::new("rm")
-            .arg("-rf")
-            .arg("ios/Pods")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/Pods".to_string()))?;
-        println!("\x1b[32m[rn-run]: ios/Pods deleted\x1b[0m");
-
-        Command::new("rm")
-            .arg("-rf")
-            .arg("ios/build")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/build".to_string()))?;
-        println!("\x1b[32m[rn-run]: ios/build deleted\x1b[0m");
-
-        Command::new("rm")
-            .arg("-rf")
-            .arg("ios/Podfile.lock")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf ios/Podfile.lock".to_string()))?;
-        println!("\x1b[32m[rn-run]: ios/Podfile.lock deleted\x1b[0m");
+        remove_logged(runner, "ios/Pods", log)?;
+        remove_logged(runner, "ios/build", log)?;
+        remove_logged(runner, "ios/Podfile.lock", log)?;
     } else if platform == "android" {
-        Command::new("rm")
-            .arg("-rf")
-            .arg("android/build")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/build".to_string()))?;
-        println!("\x1b[32m[rn-run]: android/build deleted\x1b[0m");
-
-        Command::new("rm")
-            .arg("-rf")
-            .arg("android/app/build")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/app/build".to_string()))?;
-        println!("\x1b[32m[rn-run]: android/app/build deleted\x1b[0m");
-
-        Command::new("rm")
-            .arg("-rf")
-            .arg("android/.gradle")
-            .status()
-            .map_err(|_| AppError::CommandFailed("rm -rf android/.gradle".to_string()))?;
-        println!("\x1b[32m[rn-run]: android/.gradle deleted\x1b[0m");
+        remove_logged(runner, "android/build", log)?;
+        remove_logged(runner, "android/app/build", log)?;
+        remove_logged(runner, "android/.gradle", log)?;
     }
 
     Ok(())
 }
 
-pub fn watch_directory_logged(watch_dir: &str, log: Option<&LogWriter>) -> Result<()> {
+pub fn watch_directory_logged(runner: &dyn CommandRunner, watch_dir: &str, log: Option<&LogWriter>) -> Result<()> {
     let msg = format!("Watching directory: {}", watch_dir);
     if let Some(log) = log {
         log.log(&msg);
@@ -354,160 +475,255 @@ pub fn watch_directory_logged(watch_dir: &str, log: Option<&LogWriter>) -> Resul
         println!("{}", msg);
     }
 
-    let output1 = Command::new("watchman")
-        .arg("watch-del")
-        .arg(watch_dir)
-        .output()
+    let output1 = runner
+        .output("watchman", &["watch-del", watch_dir])
         .map_err(|_| AppError::CommandFailed("watchman watch-del".to_string()))?;
 
     if let Some(log) = log {
         log.log_command_output(&output1);
     } else {
-        print!("{}", String::from_utf8_lossy(&output1.stdout));
+        print!("{}", output1.stdout_str());
     }
 
-    let output2 = Command::new("watchman")
-        .arg("watch-project")
-        .arg(watch_dir)
-        .output()
+    let output2 = runner
+        .output("watchman", &["watch-project", watch_dir])
         .map_err(|_| AppError::CommandFailed("watchman watch-project".to_string()))?;
 
     if let Some(log) = log {
         log.log_command_output(&output2);
     } else {
-        print!("{}", String::from_utf8_lossy(&output2.stdout));
+        print!("{}", output2.stdout_str());
     }
 
     Ok(())
 }
 
-pub fn watch_directory(watch_dir: &str) -> Result<()> {
-    watch_directory_logged(watch_dir, None)
-}
-
-pub fn launch_packager() -> Result<()> {
+/// Start Metro in a new terminal window, on `port` when one is configured.
+pub fn launch_packager(runner: &dyn CommandRunner, package_manager: PackageManager, port: Option<u16>) -> Result<()> {
     let current_dir = env::current_dir()
         .map_err(|_| AppError::CurrentDir)?
         .to_str()
         .ok_or(AppError::CurrentDir)?
         .to_owned();
 
-    Command::new("osascript")
-        .arg("-e")
-        .arg(format!(
-            "tell application \"Terminal\" to do script \"cd {}; yarn start\"",
-            current_dir))
-        .status()
-        .map_err(|_| AppError::CommandFailed("launch packager".to_string()))?;
-    
-    Ok(())
+    let start_command = match port {
+        Some(port) => package_manager.run_script_with_args("start", &format!("--port {}", port)),
+        None => package_manager.run_script("start"),
+    };
+    if runner.is_dry_run() {
+        return record_terminal_launch(runner, &current_dir, &start_command);
+    }
+    TerminalLauncher::detect()?.open(runner, &current_dir, &start_command)
 }
 
-pub fn launch_sim(react_native_version: &str, args: &Args, log_writer: &LogWriter) -> Result<String> {
-    let log_path = &log_writer.path;
+/// Wait for the packager rn-run just launched to answer on the configured port,
+/// so the native build doesn't start against a Metro that never came up.
+pub fn wait_for_packager(runner: &dyn CommandRunner, config: &Config, log: &LogWriter) -> Result<Option<MetroReadiness>> {
+    // A dry run never launched anything to wait for
+    if runner.is_dry_run() {
+        return Ok(None);
+    }
 
-    let yarn_ios = "yarn react-native run-ios".to_string();
-    let npx_ios = format!("npx react-native run-ios --simulator=\"{}\"", args.simulator.as_ref().unwrap_or(&"iPhone 15".to_string()));
+    let port = config.metro_port();
+    log.log(&format!("[rn-run]: Waiting for Metro on port {}", port));
+    let readiness = metro::wait_until_ready(port, metro::READY_TIMEOUT)?;
+    log.log_green(&format!("[rn-run]: Metro ready on port {} after {:.1}s", port, readiness.waited_ms as f64 / 1000.0));
+    Ok(Some(readiness))
+}
 
-    // For Android, check for preferred device (physical devices prioritized over emulators)
-    let device_flag = if args.android {
-        if let Some(device_id) = get_preferred_android_device() {
-            if !device_id.starts_with("emulator-") {
-                log_writer.log_green(&format!("[rn-run]: Physical device detected: {}", device_id));
-            }
-            format!(" --deviceId={}", device_id)
-        } else {
-            String::new()
+/// Build the react-native run command for the selected platform, wrapped with
+/// the configured hooks and the project's prebuild script when one exists.
+fn get_build_command(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    package_manager: PackageManager,
+    react_native_version: &Version,
+    platform: &str,
+    log_writer: &LogWriter,
+    current_dir: &str,
+) -> String {
+    let react_native = package_manager.exec("react-native");
+    let port_flag = config.metro.port.map(|port| format!(" --port {}", port)).unwrap_or_default();
+
+    let base_command = if platform == "ios" {
+        let scheme_flag = config.ios.scheme.as_ref().map(|scheme| format!(" --scheme \"{}\"", scheme)).unwrap_or_default();
+        let target = get_ios_target(runner, config);
+        if let IosTarget::Device(udid) = &target {
+            log_writer.log_green(&format!("[rn-run]: Physical device detected: {}", udid));
         }
+        format!("{} run-ios {}{}{}", react_native, target.flag(), scheme_flag, port_flag)
+    } else if platform == "android" {
+        // run_android has already picked the device; without one the CLI installs on every device
+        let device_flag = match &config.android.device {
+            Some(device_id) => {
+                if !device_id.starts_with("emulator-") {
+                    log_writer.log_green(&format!("[rn-run]: Physical device detected: {}", device_id));
+                }
+                format!(" --deviceId={}", device_id)
+            }
+            None => String::new(),
+        };
+
+        // RN 0.71+ renamed --variant to --mode
+        let variant_flag = match &config.android.variant {
+            Some(variant) if react_native_version.at_least(0, 71) => format!(" --mode {}", variant),
+            Some(variant) => format!(" --variant {}", variant),
+            None => String::new(),
+        };
+
+        format!("{} run-android --active-arch-only{}{}{}", react_native, device_flag, variant_flag, port_flag)
     } else {
-        String::new()
+        "echo \"No platform specified, use --help for more info\"".to_string()
     };
 
-    let yarn_android = format!("yarn react-native run-android --active-arch-only{}", device_flag);
-    let npx_android = format!("npx react-native run-android --active-arch-only{}", device_flag);
-
-    let base_command = if args.ios && is_version_greater_or_equal(react_native_version, "0.74") {
-        npx_ios
-    } else if args.ios && react_native_version.starts_with("0.69") {
-        yarn_ios
-    } else if args.android && is_version_greater_or_equal(react_native_version, "0.74") {
-        npx_android
-    } else if args.android && react_native_version.starts_with("0.69") {
-        yarn_android
+    // Check for prebuild script in package.json and prepend if it exists
+    let package_json_path = PathBuf::from(current_dir).join("package.json");
+    let mut build_command = if has_prebuild_script(&package_json_path) {
+        let prebuild = package_manager.run_script("prebuild");
+        log_writer.log_green(&format!("[rn-run]: Found prebuild script, running {} first", prebuild));
+        format!("{} && {}", prebuild, base_command)
     } else {
-        "echo \"No platform specified, use --help for more info\"".to_string()
+        base_command
     };
 
+    if let Some(hook) = &config.hooks.pre_build {
+        log_writer.log_green(&format!("[rn-run]: Running pre_build hook: {}", hook));
+        build_command = format!("{} && {}", hook, build_command);
+    }
+    if let Some(hook) = &config.hooks.post_build {
+        build_command = format!("{} && {}", build_command, hook);
+    }
+
+    build_command
+}
+
+pub fn launch_sim(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    package_manager: PackageManager,
+    react_native_version: &Version,
+    platform: &str,
+    log_writer: &LogWriter,
+) -> Result<String> {
+    let log_path = &log_writer.path;
+
     let current_dir = env::current_dir()
         .map_err(|_| AppError::CurrentDir)?
         .to_str()
         .ok_or(AppError::CurrentDir)?
         .to_owned();
 
-    // Check for prebuild script in package.json and prepend if it exists
-    let package_json_path = PathBuf::from(&current_dir).join("package.json");
-    let build_command = if has_prebuild_script(&package_json_path) {
-        log_writer.log_green("[rn-run]: Found prebuild script, running npm run prebuild first");
-        format!("npm run prebuild && {}", base_command)
-    } else {
-        base_command
-    };
+    let build_command = get_build_command(runner, config, package_manager, react_native_version, platform, log_writer, &current_dir);
 
     log_writer.log_green(&format!("[rn-run]: Logging to {}", log_path));
 
     // Use 'script' to capture output while preserving full TTY behavior (colors, animations, spinners)
-    // script -q = quiet, -a = append to existing log file
-    let escaped_build_cmd = build_command.replace("'", "'\"'\"'");
-    let script_command = format!(
-        "script -q -a '{}' bash -c '{}'",
-        log_path.replace("'", "'\"'\"'"),
-        escaped_build_cmd
-    );
-
-    let osascript_command = format!(
-        "tell application \"Terminal\" to do script \"cd {}; {}\"",
-        current_dir,
-        script_command.replace("\"", "\\\"")
-    );
-
-    Command::new("osascript")
-        .arg("-e")
-        .arg(&osascript_command)
-        .status()
-        .map_err(|_| AppError::CommandFailed("launch simulator".to_string()))?;
+    let command = with_result_marker(log_path, &script_command(log_path, &build_command));
+    if runner.is_dry_run() {
+        record_terminal_launch(runner, &current_dir, &command)?;
+        return Ok(log_path.clone());
+    }
+    let launcher = TerminalLauncher::detect()?;
+    if !is_macos() {
+        log_writer.log_green(&format!("[rn-run]: Launching build in {}", launcher.name()));
+    }
+    launcher.open(runner, &current_dir, &command)?;
 
     Ok(log_path.clone())
 }
 
+/// Append the build's result marker to the log once `command` exits, as
+/// `run_build_foreground` does, so `logs follow --until-done` can tell it finished.
+fn with_result_marker(log_path: &str, command: &str) -> String {
+    let log = shell_quote(log_path);
+    format!(
+        "{}; rn_run_status=$?; if [ $rn_run_status -eq 0 ]; then echo {} >> {}; else echo \"{}\" >> {}; fi",
+        command,
+        shell_quote(BUILD_SUCCEEDED_MARKER),
+        log,
+        build_failed_marker("$rn_run_status"),
+        log,
+    )
+}
 
-pub fn is_version_greater_or_equal(version: &str, target: &str) -> bool {
-    // Split version and target into components
-    let version_parts: Vec<&str> = version.split('.').collect();
-    let target_parts: Vec<&str> = target.split('.').collect();
-
-    // Compare major versions
-    if version_parts[0] > target_parts[0] {
-        return true;
-    } else if version_parts[0] < target_parts[0] {
-        return false;
-    }
+/// What `run_ios`/`run_android` did.
+pub struct RunOutcome {
+    /// Outcome of a foreground build; None when the build was handed off to a new terminal window
+    pub build: Option<BuildOutcome>,
+    /// Readiness of the Metro server rn-run started, if it started one
+    pub metro: Option<MetroReadiness>,
+}
 
-    // Compare minor versions if major versions are equal
-    if version_parts[1] > target_parts[1] {
-        return true;
-    } else if version_parts[1] < target_parts[1] {
-        return false;
-    }
+pub struct BuildOutcome {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub log_path: String,
+}
 
-    // Compare patch versions if both major and minor are equal (optional)
-    if version_parts.len() > 2 && target_parts.len() > 2 {
-        if version_parts[2] >= target_parts[2] {
-            return true;
+impl BuildOutcome {
+    /// The `--json` result for this build of `platform`.
+    pub fn result(&self, platform: &str) -> BuildResult {
+        let duration_secs = self.duration.as_secs_f64();
+        let message = if self.success {
+            format!("Build succeeded in {:.1}s", duration_secs)
+        } else {
+            match self.exit_code {
+                Some(code) => format!("Build failed with exit code {} after {:.1}s", code, duration_secs),
+                None => format!("Build terminated by a signal after {:.1}s", duration_secs),
+            }
+        };
+
+        BuildResult {
+            platform: platform.to_string(),
+            success: self.success,
+            exit_code: self.exit_code,
+            duration_ms: self.duration.as_millis() as u64,
+            log_path: self.log_path.clone(),
+            message,
         }
     }
+}
+
+/// Run the build as a child process of rn-run instead of a new Terminal window.
+/// Output is streamed to the console and the log file, and the call blocks
+/// until the build finishes so its exit status can be propagated.
+pub fn run_build_foreground(
+    runner: &dyn CommandRunner,
+    config: &Config,
+    package_manager: PackageManager,
+    react_native_version: &Version,
+    platform: &str,
+    log_writer: &LogWriter,
+) -> Result<BuildOutcome> {
+    let current_dir = env::current_dir()
+        .map_err(|_| AppError::CurrentDir)?
+        .to_str()
+        .ok_or(AppError::CurrentDir)?
+        .to_owned();
 
-    // If all parts are equal, the version is equal or greater
-    true
+    let build_command = get_build_command(runner, config, package_manager, react_native_version, platform, log_writer, &current_dir);
+
+    log_writer.log_green(&format!("[rn-run]: Logging to {}", log_writer.path));
+    log_writer.log_green(&format!("[rn-run]: Running in foreground: {}", build_command));
+
+    let started = Instant::now();
+    let exit_code = runner
+        .stream("sh", &["-c", &build_command], Some(&current_dir), &|pipe, is_stderr| {
+            log_writer.tee(pipe, is_stderr || log_writer.echo_stderr)
+        })
+        .map_err(|_| AppError::CommandFailed(build_command.clone()))?;
+
+    let outcome = BuildOutcome {
+        success: exit_code == Some(0),
+        exit_code,
+        duration: started.elapsed(),
+        log_path: log_writer.path.clone(),
+    };
+
+    log_writer.finish(outcome.success, outcome.exit_code);
+
+    Ok(outcome)
 }
 
 #[derive(Deserialize)]
@@ -528,18 +744,15 @@ pub struct UpdateResult {
     pub message: String,
 }
 
-pub fn check_and_update() -> Result<UpdateResult> {
+pub fn check_and_update(runner: &dyn CommandRunner) -> Result<UpdateResult> {
     let current_version = env!("CARGO_PKG_VERSION");
 
     // Fetch latest version from crates.io
-    let output = Command::new("curl")
-        .args(["-s", "https://crates.io/api/v1/crates/rn-run"])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .output()
+    let output = runner
+        .output("curl", &["-s", "https://crates.io/api/v1/crates/rn-run"])
         .map_err(|_| AppError::CommandFailed("curl crates.io".to_string()))?;
 
-    if !output.status.success() {
+    if !output.success {
         return Err(AppError::CommandFailed("Failed to fetch version info from crates.io".to_string()));
     }
 
@@ -549,7 +762,7 @@ pub fn check_and_update() -> Result<UpdateResult> {
     let latest_version = &response.krate.max_version;
 
     // Compare versions
-    if is_version_greater_or_equal(current_version, latest_version) {
+    if Version::parse(current_version)? >= Version::parse(latest_version)? {
         return Ok(UpdateResult {
             current_version: current_version.to_string(),
             latest_version: latest_version.clone(),
@@ -561,12 +774,11 @@ pub fn check_and_update() -> Result<UpdateResult> {
     // Update to latest version
     println!("\x1b[32m[rn-run]: Updating from v{} to v{}...\x1b[0m", current_version, latest_version);
 
-    let status = Command::new("cargo")
-        .args(["install", "rn-run", "--force"])
-        .status()
+    let updated = runner
+        .status("cargo", &["install", "rn-run", "--force"])
         .map_err(|_| AppError::CommandFailed("cargo install rn-run".to_string()))?;
 
-    if !status.success() {
+    if !updated {
         return Err(AppError::CommandFailed("cargo install rn-run failed".to_string()));
     }
 
@@ -587,8 +799,31 @@ pub fn get_log_dir() -> PathBuf {
     PathBuf::from(home).join(LOG_DIR)
 }
 
-pub fn ensure_log_dir() -> Result<PathBuf> {
-    let log_dir = get_log_dir();
+/// Logs of the project at `project_root`, so projects don't rotate out each other's logs.
+pub fn project_log_dir(project_root: &Path) -> PathBuf {
+    get_log_dir().join(project_log_id(project_root))
+}
+
+/// "<directory name>-<hash of the path>": readable, and distinct for two
+/// projects in directories with the same name.
+pub fn project_log_id(project_root: &Path) -> String {
+    let name: String = project_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string())
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .collect();
+    // FNV-1a, which unlike DefaultHasher is stable across Rust releases
+    let hash = project_root
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("{}-{:08x}", name, hash as u32)
+}
+
+pub fn ensure_log_dir(project_root: &Path) -> Result<PathBuf> {
+    let log_dir = project_log_dir(project_root);
     if !log_dir.exists() {
         fs::create_dir_all(&log_dir)
             .map_err(|_| AppError::CommandFailed("Failed to create log directory".to_string()))?;
@@ -596,98 +831,379 @@ pub fn ensure_log_dir() -> Result<PathBuf> {
     Ok(log_dir)
 }
 
-pub fn create_log_path(platform: &str) -> Result<String> {
-    let log_dir = ensure_log_dir()?;
-    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S");
-    let log_file = log_dir.join(format!("rn-run-{}-{}.log", platform, timestamp));
-    Ok(log_file.to_string_lossy().to_string())
+/// Log names carry the start time to the millisecond, so they sort in the
+/// order builds started whatever happens to their mtimes.
+const LOG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S%.3f";
+
+/// Create a new, empty log for `platform` in the project's log directory.
+/// Never reuses an existing file: a build started in the same millisecond
+/// gets a counter after the timestamp.
+pub fn create_log_file(platform: &str, project_root: &Path) -> Result<(String, File)> {
+    let log_dir = ensure_log_dir(project_root)?;
+    let timestamp = Local::now().format(LOG_TIMESTAMP_FORMAT).to_string();
+
+    for counter in 0..100 {
+        let name = match counter {
+            0 => format!("rn-run-{}-{}.log", platform, timestamp),
+            _ => format!("rn-run-{}-{}_{}.log", platform, timestamp, counter),
+        };
+        let path = log_dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path.to_string_lossy().to_string(), file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(_) => break,
+        }
+    }
+    Err(AppError::CommandFailed("Failed to create log file".to_string()))
+}
+
+/// Sidecar file holding a log's `LogMetadata`.
+pub fn metadata_path(log_path: &Path) -> PathBuf {
+    log_path.with_extension("json")
 }
 
-pub fn rotate_logs() -> Result<()> {
-    let log_dir = get_log_dir();
+/// When a log was started, from the timestamp and counter in its name. Logs
+/// named otherwise fall back to when they were last written.
+fn log_order(path: &Path) -> (Option<chrono::NaiveDateTime>, u32) {
+    let from_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.strip_prefix("rn-run-"))
+        .and_then(|rest| rest.split_once('-'))
+        .and_then(|(_platform, rest)| {
+            let parse = |timestamp: &str| chrono::NaiveDateTime::parse_from_str(timestamp, LOG_TIMESTAMP_FORMAT).ok();
+            match parse(rest) {
+                Some(started) => Some((started, 0)),
+                None => {
+                    let (timestamp, counter) = rest.rsplit_once('_')?;
+                    Some((parse(timestamp)?, counter.parse().ok()?))
+                }
+            }
+        });
+
+    match from_name {
+        Some((started, counter)) => (Some(started), counter),
+        None => {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            (modified.map(|modified| chrono::DateTime::<Local>::from(modified).naive_local()), 0)
+        }
+    }
+}
+
+/// Keep the `max_logs` most recent logs of one project, always including
+/// `current`, the log just started.
+pub fn rotate_logs(project_root: &Path, max_logs: usize, current: &Path) -> Result<()> {
+    let log_dir = project_log_dir(project_root);
     if !log_dir.exists() {
         return Ok(());
     }
 
-    let mut logs: Vec<_> = fs::read_dir(&log_dir)
+    let mut logs: Vec<PathBuf> = fs::read_dir(&log_dir)
         .map_err(|_| AppError::CommandFailed("Failed to read log directory".to_string()))?
         .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.path().extension().map(|e| e == "log").unwrap_or(false)
-        })
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "log").unwrap_or(false) && path != current)
         .collect();
 
-    // Sort by modification time (newest first)
-    logs.sort_by(|a, b| {
-        let a_time = a.metadata().and_then(|m| m.modified()).ok();
-        let b_time = b.metadata().and_then(|m| m.modified()).ok();
-        b_time.cmp(&a_time)
-    });
+    // Newest first
+    logs.sort_by_cached_key(|path| std::cmp::Reverse(log_order(path)));
 
-    // Delete logs beyond MAX_LOGS
-    for log in logs.iter().skip(MAX_LOGS) {
-        let _ = fs::remove_file(log.path());
+    // Delete logs beyond the retention limit, counting the current one
+    for log in logs.iter().skip(max_logs.saturating_sub(1)) {
+        let _ = fs::remove_file(log);
+        let _ = fs::remove_file(metadata_path(log));
     }
 
     Ok(())
 }
 
+/// How a logged build went.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Logs written before rn-run recorded results
+    Unknown,
+}
+
+impl LogStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogStatus::Running => "running",
+            LogStatus::Succeeded => "succeeded",
+            LogStatus::Failed => "failed",
+            LogStatus::Unknown => "unknown",
+        }
+    }
+}
+
+/// What a build log is of, stored next to it as JSON.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LogMetadata {
+    /// Name from package.json, else the directory name
+    pub project: String,
+    pub project_root: String,
+    pub platform: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub react_native_version: Option<String>,
+    /// rn-run command line that started the build
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    pub started: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub status: LogStatus,
+}
+
+impl LogMetadata {
+    /// Metadata for a build of `platform` starting now in `project_root`.
+    pub fn new(runner: &dyn CommandRunner, platform: &str, project_root: &str, react_native_version: &Version) -> Self {
+        let package_name = fs::read_to_string(Path::new(project_root).join("package.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+            .and_then(|package| package["name"].as_str().map(str::to_string));
+        let project = package_name.unwrap_or_else(|| {
+            Path::new(project_root)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| project_root.to_string())
+        });
+
+        let git_commit = runner
+            .output("git", &["-C", project_root, "rev-parse", "HEAD"])
+            .ok()
+            .filter(|output| output.success)
+            .map(|output| output.stdout_str().trim().to_string())
+            .filter(|commit| !commit.is_empty());
+
+        let command = std::iter::once("rn-run".to_string())
+            .chain(env::args().skip(1).map(|arg| {
+                if arg.contains(char::is_whitespace) { shell_quote(&arg) } else { arg }
+            }))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        LogMetadata {
+            project,
+            project_root: project_root.to_string(),
+            platform: platform.to_string(),
+            react_native_version: Some(react_native_version.to_string()),
+            command,
+            git_commit,
+            started: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            finished: None,
+            exit_code: None,
+            status: LogStatus::Running,
+        }
+    }
+
+    fn write(&self, log_path: &Path) {
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = fs::write(metadata_path(log_path), json);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct LogEntry {
     pub path: String,
     pub name: String,
     pub size: u64,
     pub modified: String,
+    pub status: LogStatus,
+    /// None for logs written before rn-run kept metadata
+    pub metadata: Option<LogMetadata>,
 }
 
+impl LogEntry {
+    /// When the build started, or for logs without metadata, when the log was last written
+    pub fn started(&self) -> Option<chrono::NaiveDateTime> {
+        let started = self.metadata.as_ref().map_or(&self.modified, |metadata| &metadata.started);
+        chrono::NaiveDateTime::parse_from_str(started, "%Y-%m-%d %H:%M:%S").ok()
+    }
+
+    /// From the metadata, else from the file name ("rn-run-ios-<timestamp>.log")
+    pub fn platform(&self) -> Option<&str> {
+        match &self.metadata {
+            Some(metadata) => Some(&metadata.platform),
+            None => self.name.strip_prefix("rn-run-")?.split('-').next(),
+        }
+    }
+}
+
+/// All build logs, newest first: each project's, and any from before logs were
+/// kept per project.
 pub fn list_logs() -> Result<Vec<LogEntry>> {
     let log_dir = get_log_dir();
     if !log_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut logs: Vec<_> = fs::read_dir(&log_dir)
-        .map_err(|_| AppError::CommandFailed("Failed to read log directory".to_string()))?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.path().extension().map(|e| e == "log").unwrap_or(false)
-        })
-        .collect();
+    let read_dir = |dir: &Path| -> Result<Vec<fs::DirEntry>> {
+        Ok(fs::read_dir(dir)
+            .map_err(|_| AppError::CommandFailed("Failed to read log directory".to_string()))?
+            .filter_map(|entry| entry.ok())
+            .collect())
+    };
+
+    let mut logs = Vec::new();
+    for entry in read_dir(&log_dir)? {
+        if entry.path().is_dir() {
+            logs.extend(read_dir(&entry.path())?);
+        } else {
+            logs.push(entry);
+        }
+    }
+    logs.retain(|entry| entry.path().extension().map(|e| e == "log").unwrap_or(false));
 
-    // Sort by modification time (newest first)
-    logs.sort_by(|a, b| {
-        let a_time = a.metadata().and_then(|m| m.modified()).ok();
-        let b_time = b.metadata().and_then(|m| m.modified()).ok();
-        b_time.cmp(&a_time)
-    });
+    // Newest first
+    logs.sort_by_cached_key(|entry| std::cmp::Reverse(log_order(&entry.path())));
 
     let entries: Vec<LogEntry> = logs
         .iter()
-        .filter_map(|entry| {
-            let path = entry.path();
-            let metadata = entry.metadata().ok()?;
-            let modified = metadata.modified().ok()?;
-            let datetime: chrono::DateTime<Local> = modified.into();
-
-            Some(LogEntry {
-                path: path.to_string_lossy().to_string(),
-                name: path.file_name()?.to_string_lossy().to_string(),
-                size: metadata.len(),
-                modified: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
-            })
-        })
+        .filter_map(|entry| log_entry(&entry.path()))
         .collect();
 
     Ok(entries)
 }
 
+fn log_entry(path: &Path) -> Option<LogEntry> {
+    let file_metadata = fs::metadata(path).ok()?;
+    let modified = file_metadata.modified().ok()?;
+    let datetime: chrono::DateTime<Local> = modified.into();
+    let modified = datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let mut metadata = fs::read_to_string(metadata_path(path))
+        .ok()
+        .and_then(|json| serde_json::from_str::<LogMetadata>(&json).ok());
+    // Builds in a terminal window only leave their result in the log itself
+    let ended = read_build_marker(path);
+    let status = match ended {
+        Some(BuildStatus::Succeeded) => LogStatus::Succeeded,
+        Some(BuildStatus::Failed(_)) => LogStatus::Failed,
+        None if metadata.is_some() => LogStatus::Running,
+        None => LogStatus::Unknown,
+    };
+    if let (Some(metadata), Some(ended)) = (&mut metadata, ended) {
+        metadata.status = status;
+        metadata.finished.get_or_insert_with(|| modified.clone());
+        if metadata.exit_code.is_none() {
+            metadata.exit_code = match ended {
+                BuildStatus::Succeeded => Some(0),
+                BuildStatus::Failed(code) => code,
+            };
+        }
+    }
+
+    Some(LogEntry {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name()?.to_string_lossy().to_string(),
+        size: file_metadata.len(),
+        modified,
+        status,
+        metadata,
+    })
+}
+
+/// The result marker in the last lines of a log, if the build has finished.
+fn read_build_marker(path: &Path) -> Option<BuildStatus> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(io::SeekFrom::Start(len.saturating_sub(1024))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    String::from_utf8_lossy(&tail).lines().rev().find_map(parse_build_marker)
+}
+
+/// Logs matching every given filter.
+pub fn filter_logs(logs: Vec<LogEntry>, filter: &LogFilterArgs) -> Result<Vec<LogEntry>> {
+    // "." is the project in the current directory
+    let current_root = match filter.project.as_deref() {
+        Some(".") => Some(get_current_directory()?),
+        _ => None,
+    };
+
+    Ok(logs
+        .into_iter()
+        .filter(|log| {
+            let project = filter.project.as_deref().is_none_or(|wanted| {
+                log.metadata.as_ref().is_some_and(|metadata| match &current_root {
+                    Some(root) => &metadata.project_root == root,
+                    None => metadata.project.eq_ignore_ascii_case(wanted) || metadata.project_root == wanted,
+                })
+            });
+            let platform = filter.platform.is_none_or(|wanted| log.platform() == Some(wanted.name()));
+            let status = filter.status.is_none_or(|wanted| log.status == wanted);
+            let commit = filter.commit.as_deref().is_none_or(|wanted| {
+                log.metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.git_commit.as_deref())
+                    .is_some_and(|commit| commit.starts_with(wanted))
+            });
+            let since = filter.since.is_none_or(|since| log.started().is_some_and(|started| started >= since));
+            project && platform && status && commit && since
+        })
+        .collect())
+}
+
+/// The log `logs show` means: number `selector` in `logs list` with the same
+/// filters, a log file name, or else the most recent log matching `filter`
+/// (the first one, with `--since`). None when no log matches.
+pub fn select_log(selector: Option<&str>, filter: &LogFilterArgs) -> Result<Option<LogEntry>> {
+    let logs = filter_logs(list_logs()?, filter)?;
+    match selector {
+        Some(index) if index.chars().all(|c| c.is_ascii_digit()) => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| index.checked_sub(1))
+            .and_then(|index| logs.into_iter().nth(index))
+            .map(Some)
+            .ok_or_else(|| AppError::LogNotFound(index.to_string())),
+        Some(name) => find_log(name).map(Some),
+        None if filter.since.is_some() => Ok(logs.into_iter().last()),
+        None => Ok(logs.into_iter().next()),
+    }
+}
+
+/// The first or last `count` lines of `content`.
+pub fn slice_lines(content: &str, head: Option<usize>, tail: Option<usize>) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let lines = match (head, tail) {
+        (Some(head), _) => &lines[..head.min(lines.len())],
+        (None, Some(tail)) => &lines[lines.len().saturating_sub(tail)..],
+        (None, None) => &lines[..],
+    };
+    lines.join("\n")
+}
+
+/// A log by its name in `logs list` (the ".log" suffix is optional), or by path.
+pub fn find_log(name: &str) -> Result<LogEntry> {
+    let with_extension = format!("{}.log", name);
+    if let Some(log) = list_logs()?.into_iter().find(|log| log.name == name || log.name == with_extension) {
+        return Ok(log);
+    }
+
+    let path = Path::new(name);
+    if path.is_file() {
+        if let Some(log) = log_entry(path) {
+            return Ok(log);
+        }
+    }
+    Err(AppError::LogNotFound(name.to_string()))
+}
+
 pub fn get_latest_log() -> Result<Option<LogEntry>> {
     let logs = list_logs()?;
     Ok(logs.into_iter().next())
 }
 
 /// Strip ANSI escape codes from text
-fn strip_ansi_codes(text: &str) -> String {
+pub fn strip_ansi_codes(text: &str) -> String {
     let mut result = String::new();
     let mut chars = text.chars().peekable();
 
@@ -719,95 +1235,190 @@ fn strip_ansi_codes(text: &str) -> String {
 /// 1. Stripping ANSI escape codes (colors, cursor movement)
 /// 2. Deduplicating progress spinner lines (- Building..., etc.)
 pub fn clean_log_content(content: &str) -> String {
-    use std::collections::HashSet;
-
-    // First strip ANSI codes
-    let clean = strip_ansi_codes(content);
+    let mut cleaner = LogCleaner::default();
+    strip_ansi_codes(content)
+        .lines()
+        .filter_map(|line| cleaner.clean_line(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-    let mut seen_progress: HashSet<String> = HashSet::new();
-    let mut result = Vec::new();
+/// Line-by-line form of `clean_log_content`, for logs read as they grow.
+/// Remembers the progress lines it has already let through.
+#[derive(Default)]
+pub struct LogCleaner {
+    seen_progress: std::collections::HashSet<String>,
+}
 
-    for line in clean.lines() {
+impl LogCleaner {
+    /// The cleaned line, or None if it is empty or a repeated progress line.
+    /// `line` must already be stripped of ANSI codes.
+    pub fn clean_line(&mut self, line: &str) -> Option<String> {
         let trimmed = line.trim();
         if trimmed.is_empty() {
-            continue;
+            return None;
         }
 
         // Check if this is a progress spinner line (starts with "- " and ends with dots)
         if trimmed.starts_with("- ") && trimmed.ends_with('.') {
             // Extract the base message without trailing dots
             let base = trimmed.trim_end_matches('.');
-            if !seen_progress.contains(base) {
-                seen_progress.insert(base.to_string());
-                result.push(format!("{}...", base));
+            if !self.seen_progress.insert(base.to_string()) {
+                // Skip duplicate progress lines
+                return None;
             }
-            // Skip duplicate progress lines
-        } else {
-            result.push(trimmed.to_string());
+            return Some(format!("{}...", base));
         }
+        Some(trimmed.to_string())
     }
+}
+
+/// Last line of a finished build's log. rn-run writes it for foreground
+/// builds and for builds launched in a new terminal window.
+pub const BUILD_SUCCEEDED_MARKER: &str = "=== rn-run build succeeded ===";
 
-    result.join("\n")
+pub fn build_failed_marker(code: &str) -> String {
+    format!("=== rn-run build failed (exit code {}) ===", code)
+}
+
+/// How a build ended, according to its log's result marker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuildStatus {
+    Succeeded,
+    /// Exit code, None when the build was killed by a signal
+    Failed(Option<i32>),
+}
+
+pub fn parse_build_marker(line: &str) -> Option<BuildStatus> {
+    let line = line.trim();
+    if line == BUILD_SUCCEEDED_MARKER {
+        return Some(BuildStatus::Succeeded);
+    }
+    let code = line
+        .strip_prefix("=== rn-run build failed (exit code ")?
+        .strip_suffix(") ===")?;
+    Some(BuildStatus::Failed(code.parse().ok()))
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // LOG WRITER - writes to both console and log file
 // ═══════════════════════════════════════════════════════════════════════════════
 
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
 use std::fs::OpenOptions;
 
 pub struct LogWriter {
     pub path: String,
+    /// Echo console output to stderr instead of stdout (keeps stdout clean for --json)
+    pub echo_stderr: bool,
+    /// Drop all output (used for --dry-run, which only prints its report)
+    quiet: bool,
+    metadata: Option<LogMetadata>,
 }
 
 impl LogWriter {
-    pub fn new(platform: &str) -> Result<Self> {
-        rotate_logs()?;
-        let path = create_log_path(platform)?;
-
-        // Create the log file with a header
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)
-            .map_err(|_| AppError::CommandFailed("Failed to create log file".to_string()))?;
+    /// Start a log in the project's log directory, with `metadata` in a sidecar
+    /// file. Rotates out the project's oldest logs beyond `max_logs`.
+    pub fn new(metadata: LogMetadata, max_logs: usize) -> Result<Self> {
+        let project_root = PathBuf::from(&metadata.project_root);
+        let platform = metadata.platform.as_str();
+        let (path, mut file) = create_log_file(platform, &project_root)?;
+
+        // Start the log with a header
 
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
         writeln!(file, "=== rn-run {} build log ===", platform).ok();
         writeln!(file, "Started: {}", timestamp).ok();
-        writeln!(file, "").ok();
+        writeln!(file).ok();
 
-        Ok(LogWriter { path })
+        metadata.write(Path::new(&path));
+        rotate_logs(&project_root, max_logs, Path::new(&path))?;
+        Ok(LogWriter { path, echo_stderr: false, quiet: false, metadata: Some(metadata) })
     }
 
-    pub fn log(&self, message: &str) {
-        // Print to console
-        println!("{}", message);
+    /// A writer that prints nothing and writes to no log file.
+    pub fn discard() -> Self {
+        LogWriter { path: "/dev/null".to_string(), echo_stderr: true, quiet: true, metadata: None }
+    }
 
-        // Append to log file
+    /// Record how the build ended: the result marker in the log, and the exit
+    /// status in the metadata.
+    pub fn finish(&self, success: bool, exit_code: Option<i32>) {
+        let finished = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.append(&format!("Finished: {}", finished));
+        if success {
+            self.append(BUILD_SUCCEEDED_MARKER);
+        } else {
+            let code = exit_code.map(|c| c.to_string()).unwrap_or_else(|| "signal".to_string());
+            self.append(&build_failed_marker(&code));
+        }
+
+        if let Some(metadata) = &self.metadata {
+            LogMetadata {
+                finished: Some(finished),
+                exit_code,
+                status: if success { LogStatus::Succeeded } else { LogStatus::Failed },
+                ..metadata.clone()
+            }
+            .write(Path::new(&self.path));
+        }
+    }
+
+    pub fn echo_to_stderr(mut self, enabled: bool) -> Self {
+        self.echo_stderr = enabled;
+        self
+    }
+
+    fn echo(&self, message: &str) {
+        if self.quiet {
+            return;
+        }
+        if self.echo_stderr {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    /// Append a line to the log file without printing it.
+    pub fn append(&self, message: &str) {
+        if self.quiet {
+            return;
+        }
         if let Ok(mut file) = OpenOptions::new().append(true).open(&self.path) {
             writeln!(file, "{}", message).ok();
         }
     }
 
+    pub fn log(&self, message: &str) {
+        // Print to console
+        self.echo(message);
+
+        // Append to log file
+        self.append(message);
+    }
+
     pub fn log_green(&self, message: &str) {
         // Print to console with green color
-        println!("\x1b[32m{}\x1b[0m", message);
+        self.echo(&format!("\x1b[32m{}\x1b[0m", message));
 
         // Append to log file (without color codes)
-        if let Ok(mut file) = OpenOptions::new().append(true).open(&self.path) {
-            writeln!(file, "{}", message).ok();
-        }
+        self.append(message);
     }
 
-    pub fn log_command_output(&self, output: &std::process::Output) {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    pub fn log_command_output(&self, output: &CommandOutput) {
+        if self.quiet {
+            return;
+        }
+        let stdout = output.stdout_str();
+        let stderr = output.stderr_str();
 
         if !stdout.is_empty() {
-            print!("{}", stdout);
+            if self.echo_stderr {
+                eprint!("{}", stdout);
+            } else {
+                print!("{}", stdout);
+            }
             if let Ok(mut file) = OpenOptions::new().append(true).open(&self.path) {
                 write!(file, "{}", stdout).ok();
             }
@@ -819,4 +1430,303 @@ impl LogWriter {
             }
         }
     }
-}
\ No newline at end of file
+
+    /// Copy a child process stream to the console and the log file as it arrives.
+    pub fn tee<R: Read>(&self, reader: R, to_stderr: bool) {
+        let mut reader = BufReader::new(reader);
+        let mut file = OpenOptions::new().append(true).open(&self.path).ok();
+        let mut line = Vec::new();
+
+        while let Ok(n) = reader.read_until(b'\n', &mut line) {
+            if n == 0 {
+                break;
+            }
+            if to_stderr {
+                let mut err = std::io::stderr().lock();
+                err.write_all(&line).ok();
+                err.flush().ok();
+            } else {
+                let mut out = std::io::stdout().lock();
+                out.write_all(&line).ok();
+                out.flush().ok();
+            }
+            if let Some(file) = file.as_mut() {
+                file.write_all(&line).ok();
+            }
+            line.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Platform;
+    use crate::runner::FakeRunner;
+    use crate::test_support::{EnvGuard, TempDir};
+
+    #[test]
+    fn test_kill_process_logged_kills_each_node_listener() {
+        let runner = FakeRunner::new()
+            .on("listeners", "5432 777\n8081 1234\n8081 5678\n")
+            .on("process 1234", "node /app/node_modules/.bin/react-native start\n")
+            .on("process 5678", "/usr/local/bin/node /app/node_modules/react-native/cli.js start\n")
+            .on("kill -TERM 1234", "")
+            .on("kill -TERM 5678", "");
+
+        let killed = kill_process_logged(&runner, 8081, Some(&LogWriter::discard())).unwrap();
+
+        let pids: Vec<u32> = killed.iter().map(|process| process.pid).collect();
+        assert_eq!(pids, [1234, 5678]);
+        assert_eq!(killed[0].command, "node /app/node_modules/.bin/react-native start");
+        assert_eq!(killed[0].signal, "SIGTERM");
+        // Signals are sent natively, the postgres on another port is never touched
+        assert!(runner.calls().iter().all(|call| !call.contains("777")));
+    }
+
+    #[test]
+    fn test_kill_process_logged_leaves_other_programs_alone() {
+        // Something other than Node on the Metro port is reported, not killed
+        let runner = FakeRunner::new()
+            .on("listeners", "8081 999\n")
+            .on("process 999", "/usr/sbin/httpd -D FOREGROUND\n");
+        match kill_process_logged(&runner, 8081, Some(&LogWriter::discard())) {
+            Err(AppError::PortInUse(8081, process)) => assert_eq!(process, "/usr/sbin/httpd -D FOREGROUND (pid 999)"),
+            other => panic!("expected PortInUse, got {:?}", other.map(|killed| killed.len())),
+        }
+        assert!(!runner.calls().iter().any(|call| call.starts_with("kill")));
+    }
+
+    #[test]
+    fn test_preferred_android_device_prefers_physical() {
+        let runner = FakeRunner::new().on(
+            "adb devices",
+            "List of devices attached\nemulator-5554\tdevice\nR58M12345\tdevice\n0A1B2C\tunauthorized\n",
+        );
+        assert_eq!(get_preferred_android_device(&runner), Some("R58M12345".to_string()));
+
+        let runner = FakeRunner::new().on("adb devices", "List of devices attached\nemulator-5554\tdevice\n");
+        assert_eq!(get_preferred_android_device(&runner), Some("emulator-5554".to_string()));
+
+        assert_eq!(get_preferred_android_device(&FakeRunner::new()), None);
+    }
+
+    #[test]
+    fn test_resolve_android_device_by_serial_or_model() {
+        // A model matches with or without underscores
+        let runner = FakeRunner::new().on(
+            "adb devices -l",
+            "List of devices attached\n\
+             R58M12345 device usb:1-1 product:beyond1 model:SM_G973F device:beyond1 transport_id:2\n\
+             33011FDH device usb:1-2 product:panther model:Pixel_7 device:panther transport_id:3\n\
+             44021FDH device usb:1-3 product:panther model:Pixel_7 device:panther transport_id:4\n\
+             0A1B2C unauthorized usb:1-4 transport_id:5\n",
+        );
+        assert_eq!(resolve_android_device(&runner, "R58M12345").unwrap(), "R58M12345");
+        assert_eq!(resolve_android_device(&runner, "sm g973f").unwrap(), "R58M12345");
+        assert!(matches!(resolve_android_device(&runner, "Pixel 7"), Err(AppError::AmbiguousDevice(_, serials)) if serials.len() == 2));
+        assert!(matches!(resolve_android_device(&runner, "0A1B2C"), Err(AppError::AndroidDeviceUnavailable(_, state)) if state == "unauthorized"));
+        assert!(matches!(resolve_android_device(&runner, "Galaxy"), Err(AppError::AndroidDeviceNotFound(_))));
+    }
+
+    #[test]
+    fn test_ios_target_preference() {
+        let devicectl = r#"{"info": {"outcome": "success"}, "result": {"devices": [
+            {"identifier": "1", "deviceProperties": {"name": "QA iPhone", "osVersionNumber": "17.2"},
+             "hardwareProperties": {"udid": "00008110-000A", "marketingName": "iPhone 14 Pro", "platform": "iOS"},
+             "connectionProperties": {"transportType": "localNetwork", "tunnelState": "disconnected"}},
+            {"identifier": "2", "deviceProperties": {"name": "Dev iPhone", "osVersionNumber": "17.4"},
+             "hardwareProperties": {"udid": "00008120-000B", "marketingName": "iPhone 15", "platform": "iOS"},
+             "connectionProperties": {"transportType": "wired", "tunnelState": "connected"}}
+        ]}}"#;
+        // Without a configured target a connected device beats the default simulator, cable before Wi-Fi
+        let runner = FakeRunner::new().on("xcrun devicectl list devices --quiet --json-output /dev/stdout", devicectl);
+        let mut config = Config::default();
+        assert_eq!(get_ios_target(&runner, &config), IosTarget::Device("00008120-000B".to_string()));
+        assert_eq!(get_ios_target(&FakeRunner::new(), &config).flag(), "--simulator=\"iPhone 15\"");
+
+        config.ios.simulator = Some("iPad Air".to_string());
+        assert_eq!(get_ios_target(&runner, &config), IosTarget::Simulator("iPad Air".to_string()));
+
+        config.ios.device = Some("QA iPhone".to_string());
+        assert_eq!(get_ios_target(&runner, &config).flag(), "--udid=00008110-000A");
+        config.ios.device = Some("Borrowed iPhone".to_string());
+        assert_eq!(get_ios_target(&runner, &config).flag(), "--device=\"Borrowed iPhone\"");
+    }
+
+    #[test]
+    fn test_remove_path_reports_freed_bytes() {
+        let dir = TempDir::new("remove");
+        dir.write("build/intermediates/classes.jar", vec![0u8; 700]);
+        dir.write("build/output.json", vec![0u8; 300]);
+        dir.write("Podfile.lock", vec![0u8; 20]);
+
+        assert_eq!(remove_path(&dir.join("build")).unwrap(), Some(1000));
+        assert_eq!(remove_path(&dir.join("Podfile.lock")).unwrap(), Some(20));
+        assert_eq!(remove_path(&dir.join("build")).unwrap(), None);
+        assert!(!dir.join("build").exists());
+    }
+
+    #[test]
+    fn test_project_log_id() {
+        // Same directory name, different projects
+        assert_ne!(project_log_id(Path::new("/work/a/app")), project_log_id(Path::new("/work/b/app")));
+        assert!(project_log_id(Path::new("/work/My App")).starts_with("My-App-"));
+    }
+
+    /// Logs for two projects with max_logs = 1: a failed then a succeeded
+    /// build of "shop", and a terminal build of "blog" followed by a running one.
+    struct TwoProjects {
+        _home: TempDir,
+        _env: EnvGuard,
+        shop: LogWriter,
+        blog: LogWriter,
+        running: LogWriter,
+    }
+
+    fn two_projects() -> TwoProjects {
+        let home = TempDir::new("logs");
+        let env = EnvGuard::home(home.path());
+        let runner = FakeRunner::new().on("git -C /work/shop rev-parse HEAD", "4f2c1a9e0b\n").allow_unscripted();
+        let version = Version::parse("0.74.3").unwrap();
+        let start = |platform: &str, root: &str| LogWriter::new(LogMetadata::new(&runner, platform, root, &version), 1).unwrap();
+
+        start("ios", "/work/shop").finish(false, Some(65));
+        let shop = start("android", "/work/shop");
+        shop.finish(true, Some(0));
+        // A build in a terminal window: only the log's marker says it ended
+        let blog = start("ios", "/work/blog");
+        blog.append("=== rn-run build failed (exit code 1) ===");
+        let running = start("android", "/work/blog");
+        TwoProjects { _home: home, _env: env, shop, blog, running }
+    }
+
+    #[test]
+    fn test_logs_rotate_per_project_and_keep_metadata() {
+        let projects = two_projects();
+
+        // max_logs = 1 rotated each project's own logs only
+        let logs = list_logs().unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].path, projects.running.path);
+        assert_eq!(logs[0].status, LogStatus::Running);
+        assert_eq!(logs[1].path, projects.shop.path);
+        let metadata = logs[1].metadata.as_ref().unwrap();
+        assert_eq!((metadata.project.as_str(), metadata.platform.as_str()), ("shop", "android"));
+        assert_eq!(metadata.git_commit.as_deref(), Some("4f2c1a9e0b"));
+        assert_eq!(metadata.react_native_version.as_deref(), Some("0.74.3"));
+        assert_eq!((metadata.status, metadata.exit_code), (LogStatus::Succeeded, Some(0)));
+        assert!(metadata.finished.is_some());
+        assert!(!Path::new(&projects.blog.path).exists());
+    }
+
+    #[test]
+    fn test_filter_logs() {
+        let projects = two_projects();
+        let (shop, running) = (projects.shop.path.as_str(), projects.running.path.as_str());
+        let filter = |filter: LogFilterArgs| {
+            filter_logs(list_logs().unwrap(), &filter).unwrap().into_iter().map(|log| log.path).collect::<Vec<_>>()
+        };
+        assert_eq!(filter(LogFilterArgs { project: Some("Shop".to_string()), ..Default::default() }), [shop]);
+        assert_eq!(filter(LogFilterArgs { status: Some(LogStatus::Running), ..Default::default() }), [running]);
+        assert_eq!(filter(LogFilterArgs { commit: Some("4f2c".to_string()), platform: Some(Platform::Android), ..Default::default() }), [shop]);
+        assert!(filter(LogFilterArgs { platform: Some(Platform::Ios), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn test_result_marker_from_terminal_build_is_picked_up() {
+        let projects = two_projects();
+        projects.running.append("=== rn-run build failed (exit code 2) ===");
+        let logs = list_logs().unwrap();
+        let metadata = logs[0].metadata.as_ref().unwrap();
+        assert_eq!((logs[0].status, metadata.exit_code), (LogStatus::Failed, Some(2)));
+    }
+
+    #[test]
+    fn test_select_log_for_show() {
+        let home = TempDir::new("show");
+        let _env = EnvGuard::home(home.path());
+        let now = std::time::SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        for (name, marker, age) in [
+            ("rn-run-ios-a.log", "=== rn-run build failed (exit code 65) ===", 3),
+            ("rn-run-android-b.log", "=== rn-run build succeeded ===", 2),
+            ("rn-run-ios-c.log", "=== rn-run build succeeded ===", 1),
+        ] {
+            let path = home.write(Path::new(".rn-run/logs/app-12345678").join(name), format!("line 1\nline 2\nline 3\n{}\n", marker));
+            File::options().write(true).open(&path).unwrap().set_modified(now - hour * age).unwrap();
+        }
+
+        let select = |selector: Option<&str>, filter: LogFilterArgs| select_log(selector, &filter).unwrap().unwrap().name;
+        assert_eq!(select(None, LogFilterArgs::default()), "rn-run-ios-c.log");
+        assert_eq!(select(Some("2"), LogFilterArgs::default()), "rn-run-android-b.log");
+        assert_eq!(select(Some("rn-run-ios-a"), LogFilterArgs::default()), "rn-run-ios-a.log");
+        assert_eq!(select(None, LogFilterArgs { platform: Some(Platform::Android), ..Default::default() }), "rn-run-android-b.log");
+        assert_eq!(select(None, LogFilterArgs { status: Some(LogStatus::Failed), ..Default::default() }), "rn-run-ios-a.log");
+        // --since picks the first build after it
+        let since = crate::args::parse_since("150m").unwrap();
+        assert_eq!(select(None, LogFilterArgs { since: Some(since), ..Default::default() }), "rn-run-android-b.log");
+        // Numbers count within the filtered listing
+        assert_eq!(select(Some("1"), LogFilterArgs { platform: Some(Platform::Ios), status: Some(LogStatus::Failed), ..Default::default() }), "rn-run-ios-a.log");
+        assert!(matches!(select_log(Some("4"), &LogFilterArgs::default()), Err(AppError::LogNotFound(_))));
+        assert!(matches!(select_log(Some("0"), &LogFilterArgs::default()), Err(AppError::LogNotFound(_))));
+        assert!(select_log(None, &LogFilterArgs { status: Some(LogStatus::Running), ..Default::default() }).unwrap().is_none());
+    }
+
+    fn android_build(runner: &FakeRunner) -> (BuildOutcome, String) {
+        let config = Config { android: crate::config::AndroidConfig { device: Some("emulator-5554".to_string()), ..Default::default() }, ..Default::default() };
+        let version = Version::parse("0.74.3").unwrap();
+        let log = LogWriter::new(LogMetadata::new(runner, "android", "/work/shop", &version), 5).unwrap().echo_to_stderr(true);
+        let outcome = run_build_foreground(runner, &config, PackageManager::Npm, &version, "android", &log).unwrap();
+        let contents = fs::read_to_string(&outcome.log_path).unwrap();
+        (outcome, contents)
+    }
+
+    const ANDROID_BUILD: &str = "sh -c npx react-native run-android --active-arch-only --deviceId=emulator-5554";
+
+    #[test]
+    fn test_failed_foreground_build_is_logged_and_reported() {
+        let home = TempDir::new("foreground-build");
+        let _env = EnvGuard::home(home.path());
+        let runner = FakeRunner::new().on_fail(ANDROID_BUILD, "FAILURE: Build failed with an exception.\n");
+        let (outcome, contents) = android_build(&runner);
+
+        assert!(runner.calls().iter().any(|call| call == ANDROID_BUILD));
+        assert_eq!((outcome.success, outcome.exit_code), (false, Some(1)));
+        assert!(contents.contains("Running in foreground: npx react-native run-android"));
+        assert!(contents.contains("FAILURE: Build failed with an exception."));
+        assert!(contents.contains(&build_failed_marker("1")));
+        let metadata = list_logs().unwrap().remove(0).metadata.unwrap();
+        assert_eq!((metadata.status, metadata.exit_code), (LogStatus::Failed, Some(1)));
+
+        let json = serde_json::to_value(outcome.result("android")).unwrap();
+        assert_eq!(json["success"], false);
+        assert_eq!(json["exit_code"], 1);
+        assert_eq!(json["duration_ms"], outcome.duration.as_millis() as u64);
+        assert_eq!(json["log_path"], outcome.log_path.as_str());
+        assert!(json["message"].as_str().unwrap().starts_with("Build failed with exit code 1 after"));
+    }
+
+    #[test]
+    fn test_successful_foreground_build_is_logged_and_reported() {
+        let home = TempDir::new("foreground-build");
+        let _env = EnvGuard::home(home.path());
+        let runner = FakeRunner::new().on(ANDROID_BUILD, "BUILD SUCCESSFUL in 42s\n");
+        let (outcome, contents) = android_build(&runner);
+
+        assert_eq!((outcome.success, outcome.exit_code), (true, Some(0)));
+        assert!(contents.contains("BUILD SUCCESSFUL in 42s"));
+        assert!(contents.contains(BUILD_SUCCEEDED_MARKER));
+
+        let json = serde_json::to_value(outcome.result("android")).unwrap();
+        assert_eq!((json["success"].as_bool(), json["exit_code"].as_i64()), (Some(true), Some(0)));
+        assert_eq!(json["log_path"], outcome.log_path.as_str());
+    }
+
+    #[test]
+    fn test_slice_lines() {
+        assert_eq!(slice_lines("1\n2\n3\n4", Some(2), None), "1\n2");
+        assert_eq!(slice_lines("1\n2\n3\n4", None, Some(3)), "2\n3\n4");
+        assert_eq!(slice_lines("1\n2", None, Some(9)), "1\n2");
+    }
+}
@@ -0,0 +1,222 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use crate::error::{AppError, Result};
+use crate::utils::get_react_native_version;
+
+/// Semantic version (major.minor.patch with optional prerelease).
+/// Missing components default to 0, so "0.74" parses as 0.74.0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl Version {
+    /// Parse a concrete version such as "0.74.3", "v1.2" or "0.75.0-rc.2+build".
+    pub fn parse(input: &str) -> Result<Version> {
+        let invalid = || AppError::InvalidVersion(input.to_string());
+
+        let trimmed = input.trim().trim_start_matches(['v', '=']);
+        // Build metadata does not take part in comparisons
+        let trimmed = trimmed.split('+').next().unwrap_or("");
+        let (core, pre) = match trimmed.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return Err(invalid()),
+            None => (trimmed, None),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.is_empty() || parts.len() > 3 {
+            return Err(invalid());
+        }
+
+        let mut numbers = [0u64; 3];
+        for (i, part) in parts.iter().enumerate() {
+            numbers[i] = match *part {
+                // Range wildcards (0.74.x) resolve to the lowest matching version
+                "x" | "X" | "*" if i > 0 => 0,
+                _ => part.parse().map_err(|_| invalid())?,
+            };
+        }
+
+        Ok(Version {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            pre,
+        })
+    }
+
+    /// Parse a package.json dependency specifier into the lowest version it allows.
+    /// Handles ranges ("^0.74.3", "~0.73.0", ">=0.72 <0.75") and npm aliases
+    /// ("npm:react-native-tvos@0.74.1-0").
+    pub fn from_specifier(specifier: &str) -> Result<Version> {
+        let spec = specifier.trim();
+
+        let spec = match spec.strip_prefix("npm:") {
+            Some(alias) => alias.rsplit_once('@').map(|(_, v)| v).ok_or_else(|| AppError::InvalidVersion(specifier.to_string()))?,
+            None => spec,
+        };
+
+        // Use the first comparator of the first range set
+        let comparator = spec
+            .split("||")
+            .next()
+            .and_then(|set| set.split_whitespace().next())
+            .unwrap_or("");
+        let comparator = comparator.trim_start_matches(['^', '~', '>', '<', '=']);
+
+        Version::parse(comparator).map_err(|_| AppError::InvalidVersion(specifier.to_string()))
+    }
+
+    /// Whether this is at least `major.minor`, ignoring patch and prerelease
+    /// (so 0.74.0-rc.1 counts as 0.74).
+    pub fn at_least(&self, major: u64, minor: u64) -> bool {
+        (self.major, self.minor) >= (major, minor)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| compare_prerelease(self.pre.as_deref(), other.pre.as_deref()))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Semver prerelease precedence: a release is newer than any of its prereleases,
+/// numeric identifiers compare numerically and rank below alphanumeric ones.
+fn compare_prerelease(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let mut a_ids = a.split('.');
+            let mut b_ids = b.split('.');
+            loop {
+                match (a_ids.next(), b_ids.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(x), Some(y)) => {
+                        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                            (Ok(x), Ok(y)) => x.cmp(&y),
+                            (Ok(_), Err(_)) => Ordering::Less,
+                            (Err(_), Ok(_)) => Ordering::Greater,
+                            (Err(_), Err(_)) => x.cmp(y),
+                        };
+                        if ordering != Ordering::Equal {
+                            return ordering;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// React Native version of a project and where it was resolved from.
+pub struct ReactNativeVersion {
+    pub version: Version,
+    /// Raw dependency specifier from package.json (e.g. "^0.74.3")
+    pub specifier: String,
+    /// "node_modules" when read from the installed package, otherwise "package.json"
+    pub source: String,
+}
+
+#[derive(Deserialize)]
+struct InstalledPackage {
+    version: String,
+}
+
+/// Resolve the project's React Native version, preferring the installed
+/// node_modules/react-native/package.json over the range in package.json.
+pub fn resolve_react_native_version(project_dir: &Path) -> Result<ReactNativeVersion> {
+    let specifier = get_react_native_version(&project_dir.join("package.json"))?
+        .ok_or(AppError::ReactNativeNotFound)?;
+
+    let installed = fs::read_to_string(project_dir.join("node_modules/react-native/package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<InstalledPackage>(&contents).ok())
+        .and_then(|package| Version::parse(&package.version).ok());
+
+    if let Some(version) = installed {
+        return Ok(ReactNativeVersion { version, specifier, source: "node_modules".to_string() });
+    }
+
+    let version = Version::from_specifier(&specifier)?;
+    Ok(ReactNativeVersion { version, specifier, source: "package.json".to_string() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_version_parsing_and_ordering() {
+        let v = |s: &str| Version::parse(s).unwrap();
+
+        assert!(v("0.100.0") > v("0.74.3"));
+        assert!(v("0.74") == v("0.74.0"));
+        assert!(v("0.75.0-rc.2") < v("0.75.0"));
+        assert!(v("0.75.0-rc.2") < v("0.75.0-rc.10"));
+        assert!(v("0.75.0-rc.1").at_least(0, 75));
+        assert!(!v("0.73.9").at_least(0, 74));
+        assert_eq!(v("1").to_string(), "1.0.0");
+
+        assert!(matches!(Version::parse("x.74"), Err(AppError::InvalidVersion(_))));
+        assert!(Version::parse("").is_err());
+    }
+
+    #[test]
+    fn test_version_from_specifier() {
+        let spec = |s: &str| Version::from_specifier(s).unwrap().to_string();
+        assert_eq!(spec("^0.74.3"), "0.74.3");
+        assert_eq!(spec("~0.73.0"), "0.73.0");
+        assert_eq!(spec(">=0.72.0 <0.75"), "0.72.0");
+        assert_eq!(spec("0.74.x"), "0.74.0");
+        assert_eq!(spec("npm:react-native-tvos@0.74.1-0"), "0.74.1-0");
+
+        assert!(matches!(Version::from_specifier("latest"), Err(AppError::InvalidVersion(_))));
+    }
+
+    #[test]
+    fn test_resolve_react_native_version_prefers_installed() {
+        let dir = TempDir::new("version");
+        std::fs::create_dir_all(dir.join("node_modules/react-native")).unwrap();
+        dir.write("package.json", r#"{"dependencies": {"react-native": "^0.74.1"}}"#);
+
+        let resolved = resolve_react_native_version(dir.path()).unwrap();
+        assert_eq!(resolved.version.to_string(), "0.74.1");
+        assert_eq!(resolved.source, "package.json");
+
+        dir.write("node_modules/react-native/package.json", r#"{"version": "0.74.5"}"#);
+        let resolved = resolve_react_native_version(dir.path()).unwrap();
+        assert_eq!(resolved.version.to_string(), "0.74.5");
+        assert_eq!(resolved.specifier, "^0.74.1");
+        assert_eq!(resolved.source, "node_modules");
+    }
+}